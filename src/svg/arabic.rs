@@ -0,0 +1,225 @@
+use unicode_joining_type::{get_joining_type, JoiningType};
+
+/// Contextual form of a glyph, as given by the `arabic-form` glyph attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArabicForm {
+    Isolated,
+    Initial,
+    Medial,
+    Final,
+}
+
+impl ArabicForm {
+    pub fn parse(value: &str) -> Option<ArabicForm> {
+        match value {
+            "isolated" => Some(ArabicForm::Isolated),
+            "initial" => Some(ArabicForm::Initial),
+            "medial" => Some(ArabicForm::Medial),
+            "final" => Some(ArabicForm::Final),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArabicForm::Isolated => "isolated",
+            ArabicForm::Initial => "initial",
+            ArabicForm::Medial => "medial",
+            ArabicForm::Final => "final",
+        }
+    }
+
+    fn from_joins(joins_previous: bool, joins_next: bool) -> ArabicForm {
+        match (joins_previous, joins_next) {
+            (true, true) => ArabicForm::Medial,
+            (true, false) => ArabicForm::Final,
+            (false, true) => ArabicForm::Initial,
+            (false, false) => ArabicForm::Isolated,
+        }
+    }
+}
+
+/// How a character connects to its neighbours once joining has been resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Joining {
+    pub previous: bool,
+    pub next: bool,
+}
+
+impl Joining {
+    pub fn form(&self) -> ArabicForm {
+        ArabicForm::from_joins(self.previous, self.next)
+    }
+}
+
+/// Resolves, for every character of a logical-order sequence, whether it joins
+/// the previous and the next one. Characters that don't join at all (latin
+/// letters, transparent marks...) get `None`.
+pub fn joinings(characters: &[char]) -> Vec<Option<Joining>> {
+    let types = characters
+        .iter()
+        .map(|c| get_joining_type(*c))
+        .collect::<Vec<JoiningType>>();
+
+    (0..characters.len())
+        .map(|i| {
+            if !joins_previous(types[i]) && !joins_next(types[i]) {
+                return None;
+            }
+
+            let previous = types[..i]
+                .iter()
+                .rev()
+                .find(|t| **t != JoiningType::Transparent);
+            let next = types[i + 1..]
+                .iter()
+                .find(|t| **t != JoiningType::Transparent);
+
+            Some(Joining {
+                previous: joins_previous(types[i]) && previous.is_some_and(|t| joins_next(*t)),
+                next: joins_next(types[i]) && next.is_some_and(|t| joins_previous(*t)),
+            })
+        })
+        .collect()
+}
+
+/// Returns the Arabic Presentation Forms-B character for `character` in the
+/// given form, used by fonts keying contextual glyphs or ligatures on them.
+pub fn presentation_form(character: char, form: ArabicForm) -> Option<char> {
+    let (start, count) = PRESENTATION_FORMS
+        .iter()
+        .find(|(base, _, _)| *base == character)
+        .map(|(_, start, count)| (*start, *count))?;
+
+    let offset = match form {
+        ArabicForm::Isolated => 0,
+        ArabicForm::Final => 1,
+        ArabicForm::Initial => 2,
+        ArabicForm::Medial => 3,
+    };
+
+    if offset >= count {
+        return None;
+    }
+
+    char::from_u32(start + offset)
+}
+
+// Characters "join previous" when they connect on their right side, as
+// Arabic is written right to left.
+fn joins_previous(joining_type: JoiningType) -> bool {
+    matches!(
+        joining_type,
+        JoiningType::DualJoining | JoiningType::RightJoining | JoiningType::JoinCausing
+    )
+}
+
+fn joins_next(joining_type: JoiningType) -> bool {
+    matches!(
+        joining_type,
+        JoiningType::DualJoining | JoiningType::LeftJoining | JoiningType::JoinCausing
+    )
+}
+
+/// Base letter, first presentation form (isolated) and number of forms, which
+/// follow each other in the isolated, final, initial, medial order.
+const PRESENTATION_FORMS: [(char, u32, u32); 37] = [
+    ('\u{621}', 0xfe80, 1),
+    ('\u{622}', 0xfe81, 2),
+    ('\u{623}', 0xfe83, 2),
+    ('\u{624}', 0xfe85, 2),
+    ('\u{625}', 0xfe87, 2),
+    ('\u{626}', 0xfe89, 4),
+    ('\u{627}', 0xfe8d, 2),
+    ('\u{628}', 0xfe8f, 4),
+    ('\u{629}', 0xfe93, 2),
+    ('\u{62a}', 0xfe95, 4),
+    ('\u{62b}', 0xfe99, 4),
+    ('\u{62c}', 0xfe9d, 4),
+    ('\u{62d}', 0xfea1, 4),
+    ('\u{62e}', 0xfea5, 4),
+    ('\u{62f}', 0xfea9, 2),
+    ('\u{630}', 0xfeab, 2),
+    ('\u{631}', 0xfead, 2),
+    ('\u{632}', 0xfeaf, 2),
+    ('\u{633}', 0xfeb1, 4),
+    ('\u{634}', 0xfeb5, 4),
+    ('\u{635}', 0xfeb9, 4),
+    ('\u{636}', 0xfebd, 4),
+    ('\u{637}', 0xfec1, 4),
+    ('\u{638}', 0xfec5, 4),
+    ('\u{639}', 0xfec9, 4),
+    ('\u{63a}', 0xfecd, 4),
+    ('\u{641}', 0xfed1, 4),
+    ('\u{642}', 0xfed5, 4),
+    ('\u{643}', 0xfed9, 4),
+    ('\u{644}', 0xfedd, 4),
+    ('\u{645}', 0xfee1, 4),
+    ('\u{646}', 0xfee5, 4),
+    ('\u{647}', 0xfee9, 4),
+    ('\u{648}', 0xfeed, 2),
+    ('\u{649}', 0xfeef, 2),
+    ('\u{64a}', 0xfef1, 4),
+    ('\u{671}', 0xfb50, 2),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(text: &str) -> Vec<Option<ArabicForm>> {
+        joinings(&text.chars().collect::<Vec<char>>())
+            .into_iter()
+            .map(|joining| joining.map(|j| j.form()))
+            .collect()
+    }
+
+    #[test]
+    fn it_resolves_contextual_forms() {
+        // beh, beh, beh
+        assert_eq!(
+            forms("\u{628}\u{628}\u{628}"),
+            vec![
+                Some(ArabicForm::Initial),
+                Some(ArabicForm::Medial),
+                Some(ArabicForm::Final)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_stops_joining_after_right_joining_letters() {
+        // beh, alef, beh: alef never joins the following letter
+        assert_eq!(
+            forms("\u{628}\u{627}\u{628}"),
+            vec![
+                Some(ArabicForm::Initial),
+                Some(ArabicForm::Final),
+                Some(ArabicForm::Isolated)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_skips_transparent_marks() {
+        // beh, fatha, beh
+        assert_eq!(
+            forms("\u{628}\u{64e}\u{628}"),
+            vec![Some(ArabicForm::Initial), None, Some(ArabicForm::Final)]
+        );
+    }
+
+    #[test]
+    fn it_finds_presentation_forms() {
+        assert_eq!(
+            presentation_form('\u{644}', ArabicForm::Initial),
+            Some('\u{fedf}')
+        );
+        assert_eq!(
+            presentation_form('\u{627}', ArabicForm::Final),
+            Some('\u{fe8e}')
+        );
+        assert_eq!(presentation_form('\u{627}', ArabicForm::Medial), None);
+        assert_eq!(presentation_form('a', ArabicForm::Isolated), None);
+    }
+}
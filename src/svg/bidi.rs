@@ -0,0 +1,117 @@
+use std::ops::Range;
+
+use unicode_bidi::{BidiInfo, Level};
+
+/// Base direction of a paragraph, `Auto` picking it from the first strong character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+/// A directional run of text, already in visual (left to right) order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisualRun {
+    /// The run characters in display order, mirrored when the run is right-to-left.
+    pub text: String,
+    /// Byte range of the run in the logical text.
+    pub range: Range<usize>,
+    pub rtl: bool,
+}
+
+impl VisualRun {
+    /// Characters of the run in display order with their byte offset in the
+    /// logical text.
+    pub fn characters(&self, text: &str) -> Vec<(usize, char)> {
+        visual_characters(text, &self.range, self.rtl)
+    }
+}
+
+/// Applies the Unicode Bidirectional Algorithm (UAX #9) to `text` and returns
+/// its runs in visual order, paragraph after paragraph.
+pub fn visual_runs(text: &str, direction: Direction) -> Vec<VisualRun> {
+    let level = match direction {
+        Direction::Auto => None,
+        Direction::Ltr => Some(Level::ltr()),
+        Direction::Rtl => Some(Level::rtl()),
+    };
+
+    let bidi = BidiInfo::new(text, level);
+    let mut runs = Vec::new();
+
+    for paragraph in &bidi.paragraphs {
+        let (levels, level_runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+
+        for range in level_runs {
+            let rtl = levels[range.start].is_rtl();
+
+            runs.push(VisualRun {
+                text: visual_characters(text, &range, rtl)
+                    .into_iter()
+                    .map(|(_, character)| character)
+                    .collect(),
+                range,
+                rtl,
+            });
+        }
+    }
+
+    runs
+}
+
+fn visual_characters(text: &str, range: &Range<usize>, rtl: bool) -> Vec<(usize, char)> {
+    let characters = text[range.clone()]
+        .char_indices()
+        .map(|(index, character)| (range.start + index, character));
+
+    if rtl {
+        characters
+            .rev()
+            .map(|(index, character)| (index, mirror(character)))
+            .collect()
+    } else {
+        characters.collect()
+    }
+}
+
+pub(crate) fn mirror(character: char) -> char {
+    unicode_bidi_mirroring::get_mirrored(character).unwrap_or(character)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visual_text(text: &str, direction: Direction) -> String {
+        visual_runs(text, direction)
+            .into_iter()
+            .map(|run| run.text)
+            .collect()
+    }
+
+    #[test]
+    fn it_keeps_latin_text_untouched() {
+        assert_eq!(
+            visual_text("Hello (World)", Direction::Auto),
+            "Hello (World)"
+        );
+    }
+
+    #[test]
+    fn it_reverses_hebrew_runs_inside_latin_text() {
+        assert_eq!(visual_text("abc אבג def", Direction::Ltr), "abc גבא def");
+    }
+
+    #[test]
+    fn it_mirrors_brackets_in_right_to_left_runs() {
+        assert_eq!(visual_text("אב(ג)", Direction::Auto), "(ג)בא");
+    }
+
+    #[test]
+    fn it_uses_the_base_direction_for_neutral_characters() {
+        assert_eq!(visual_text("abc אבג!", Direction::Ltr), "abc גבא!");
+        assert_eq!(visual_text("abc אבג!", Direction::Rtl), "!גבא abc");
+    }
+}
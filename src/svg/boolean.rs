@@ -0,0 +1,537 @@
+use std::collections::{HashMap, HashSet};
+
+use super::builder::PathBuilder;
+use super::hit::{winding_number, FillRule};
+use super::path::{Path, Point};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOperation {
+    Union,
+    Intersection,
+    /// The first path without the second one.
+    Difference,
+    Xor,
+}
+
+impl BooleanOperation {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOperation::Union => a || b,
+            BooleanOperation::Intersection => a && b,
+            BooleanOperation::Difference => a && !b,
+            BooleanOperation::Xor => a != b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BooleanOptions {
+    /// Fill rule of the path the operation is called on.
+    pub fill_rule: FillRule,
+    /// Fill rule of the other path.
+    pub other_fill_rule: FillRule,
+    /// Distance the result may stray from the exact shape.
+    pub tolerance: f64,
+}
+
+impl Default for BooleanOptions {
+    fn default() -> Self {
+        BooleanOptions {
+            fill_rule: FillRule::NonZero,
+            other_fill_rule: FillRule::NonZero,
+            tolerance: 0.1,
+        }
+    }
+}
+
+/// Relative tolerance of intersections, and distance from edges at which the
+/// inside is probed, relative to the size of the shapes.
+const EPSILON: f64 = 1e-9;
+const PROBE_DISTANCE: f64 = 1e-7;
+
+impl Path {
+    /// Combines the areas painted by the two paths. Curves are flattened
+    /// within half the tolerance and fitted back within the other half.
+    ///
+    /// The result doesn't overlap itself, outer contours running
+    /// counterclockwise and holes clockwise, so that both fill rules paint it
+    /// alike.
+    pub fn boolean(
+        &self,
+        other: &Path,
+        operation: BooleanOperation,
+        options: &BooleanOptions,
+    ) -> Path {
+        let tolerance = options.tolerance.abs().max(f64::EPSILON) / 2.0;
+        let polygons = polygons_of(self, tolerance);
+        let other_polygons = polygons_of(other, tolerance);
+
+        let edges = polygons
+            .iter()
+            .chain(&other_polygons)
+            .flat_map(|polygon| {
+                let points = &polygon.points;
+
+                (0..points.len()).map(move |i| (points[i], points[(i + 1) % points.len()]))
+            })
+            .filter(|(from, to)| from != to)
+            .collect::<Vec<(Point, Point)>>();
+
+        let extent = edges
+            .iter()
+            .flat_map(|(from, to)| [from.0.abs(), from.1.abs(), to.0.abs(), to.1.abs()])
+            .fold(1.0, f64::max);
+
+        let inside = |point: Point| {
+            operation.apply(
+                options.fill_rule.is_inside(winding(&polygons, point)),
+                options
+                    .other_fill_rule
+                    .is_inside(winding(&other_polygons, point)),
+            )
+        };
+
+        // Keeps the edges between the inside and the outside of the result,
+        // with the inside on their left.
+        let mut kept = Vec::new();
+
+        for (from, to) in split_edges(edges, extent) {
+            let length = (to.0 - from.0).hypot(to.1 - from.1);
+            let probe = PROBE_DISTANCE * extent;
+            let normal = (-(to.1 - from.1) / length, (to.0 - from.0) / length);
+            let middle = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+
+            let left = inside((middle.0 + normal.0 * probe, middle.1 + normal.1 * probe));
+            let right = inside((middle.0 - normal.0 * probe, middle.1 - normal.1 * probe));
+
+            match (left, right) {
+                (true, false) => kept.push((from, to)),
+                (false, true) => kept.push((to, from)),
+                _ => {}
+            }
+        }
+
+        let mut builder = PathBuilder::new();
+
+        for contour in contours(&kept) {
+            builder = builder.move_to(contour[0]);
+
+            for point in &contour[1..] {
+                builder = builder.line_to(*point);
+            }
+
+            builder = builder.close();
+        }
+
+        builder.build().simplify(tolerance)
+    }
+
+    pub fn union(&self, other: &Path, options: &BooleanOptions) -> Path {
+        self.boolean(other, BooleanOperation::Union, options)
+    }
+
+    pub fn intersection(&self, other: &Path, options: &BooleanOptions) -> Path {
+        self.boolean(other, BooleanOperation::Intersection, options)
+    }
+
+    pub fn difference(&self, other: &Path, options: &BooleanOptions) -> Path {
+        self.boolean(other, BooleanOperation::Difference, options)
+    }
+
+    pub fn xor(&self, other: &Path, options: &BooleanOptions) -> Path {
+        self.boolean(other, BooleanOperation::Xor, options)
+    }
+
+    /// The outline of the area the path paints, without overlapping contours,
+    /// such as the glyphs of `Font::text_path` merged together.
+    pub fn remove_overlaps(&self, options: &BooleanOptions) -> Path {
+        self.union(&Path::default(), options)
+    }
+}
+
+/// A flattened subpath, closed as when filling, with its bounding box.
+struct Polygon {
+    points: Vec<Point>,
+    min: Point,
+    max: Point,
+}
+
+fn polygons_of(path: &Path, tolerance: f64) -> Vec<Polygon> {
+    path.flatten(tolerance)
+        .map(|polyline| {
+            let (mut min, mut max) = (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            );
+
+            for (x, y) in &polyline.points {
+                min = (min.0.min(*x), min.1.min(*y));
+                max = (max.0.max(*x), max.1.max(*y));
+            }
+
+            Polygon {
+                points: polyline.points,
+                min,
+                max,
+            }
+        })
+        .collect()
+}
+
+/// The winding number of the polygons around a point, skipping those whose
+/// bounding box doesn't contain it.
+fn winding(polygons: &[Polygon], point: Point) -> i32 {
+    polygons
+        .iter()
+        .filter(|polygon| {
+            point.0 >= polygon.min.0
+                && point.0 <= polygon.max.0
+                && point.1 >= polygon.min.1
+                && point.1 <= polygon.max.1
+        })
+        .map(|polygon| winding_number(&polygon.points, point))
+        .sum()
+}
+
+/// Splits the edges where they cross or touch each other, so that they only
+/// meet at their ends. Edges found twice are kept once.
+///
+/// The edges are swept from left to right, only those whose horizontal spans
+/// overlap being tested against each other.
+fn split_edges(edges: Vec<(Point, Point)>, extent: f64) -> Vec<(Point, Point)> {
+    let epsilon = EPSILON * extent;
+    let mut splits = vec![Vec::new(); edges.len()];
+
+    let mut order = (0..edges.len()).collect::<Vec<usize>>();
+    order.sort_by(|i, j| min_x(edges[*i]).total_cmp(&min_x(edges[*j])));
+
+    let mut active = Vec::<usize>::new();
+
+    for i in order {
+        let (a, b) = edges[i];
+        let left = a.0.min(b.0) - epsilon;
+
+        active.retain(|j| max_x(edges[*j]) >= left);
+
+        for &j in &active {
+            let (c, d) = edges[j];
+
+            if a.1.max(b.1) < c.1.min(d.1) - epsilon || c.1.max(d.1) < a.1.min(b.1) - epsilon {
+                continue;
+            }
+
+            for point in intersections((a, b), (c, d), epsilon) {
+                if point != a && point != b {
+                    splits[i].push(point);
+                }
+
+                if point != c && point != d {
+                    splits[j].push(point);
+                }
+            }
+        }
+
+        active.push(i);
+    }
+
+    let mut split = Vec::new();
+    let mut seen = HashSet::new();
+
+    for ((from, to), mut points) in edges.into_iter().zip(splits) {
+        let along = |point: &Point| {
+            (point.0 - from.0) * (to.0 - from.0) + (point.1 - from.1) * (to.1 - from.1)
+        };
+
+        points.sort_by(|p, q| along(p).total_cmp(&along(q)));
+        points.dedup();
+        points.push(to);
+
+        let mut start = from;
+
+        for point in points {
+            if point == start {
+                continue;
+            }
+
+            // The same edge may come from overlapping contours.
+            let key = if key(start) < key(point) {
+                (key(start), key(point))
+            } else {
+                (key(point), key(start))
+            };
+
+            if seen.insert(key) {
+                split.push((start, point));
+            }
+
+            start = point;
+        }
+    }
+
+    split
+}
+
+/// The points where two edges meet: their crossing point, or the ends of
+/// each one lying on the other.
+fn intersections((a, b): (Point, Point), (c, d): (Point, Point), epsilon: f64) -> Vec<Point> {
+    let ab = (b.0 - a.0, b.1 - a.1);
+    let cd = (d.0 - c.0, d.1 - c.1);
+    let ac = (c.0 - a.0, c.1 - a.1);
+    let denominator = cross(ab, cd);
+    let (length_ab, length_cd) = (ab.0.hypot(ab.1), cd.0.hypot(cd.1));
+
+    let on = |point: Point, (from, to): (Point, Point), length: f64| {
+        let t = ((point.0 - from.0) * (to.0 - from.0) + (point.1 - from.1) * (to.1 - from.1))
+            / (length * length);
+        let distance = cross(
+            (to.0 - from.0, to.1 - from.1),
+            (point.0 - from.0, point.1 - from.1),
+        )
+        .abs()
+            / length;
+
+        distance <= epsilon && t > 0.0 && t < 1.0
+    };
+
+    // Ends touching the other edge are used as they are, keeping edges
+    // connected.
+    let touching = [
+        (a, on(a, (c, d), length_cd)),
+        (b, on(b, (c, d), length_cd)),
+        (c, on(c, (a, b), length_ab)),
+        (d, on(d, (a, b), length_ab)),
+    ]
+    .into_iter()
+    .filter(|(_, on)| *on)
+    .map(|(point, _)| point)
+    .collect::<Vec<Point>>();
+
+    if !touching.is_empty() || denominator.abs() <= EPSILON * length_ab * length_cd {
+        return touching;
+    }
+
+    let t = cross(ac, cd) / denominator;
+    let u = cross(ac, ab) / denominator;
+
+    if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+        vec![(a.0 + ab.0 * t, a.1 + ab.1 * t)]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Chains directed edges into closed contours. Where several edges leave a
+/// point, the one turning most to the left is followed, keeping contours
+/// which only touch at a point apart. Chains which don't close are dropped.
+fn contours(edges: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let mut outgoing = HashMap::<(u64, u64), Vec<usize>>::new();
+
+    for (i, (from, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(*from)).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+
+        let mut contour = vec![edges[first].0];
+        let mut edge = first;
+        used[first] = true;
+
+        let closed = loop {
+            let (from, end) = edges[edge];
+
+            if end == contour[0] {
+                break true;
+            }
+
+            let direction = (end.0 - from.0, end.1 - from.1);
+            let turn = |i: &usize| {
+                let (_, to) = edges[*i];
+                let next = (to.0 - end.0, to.1 - end.1);
+
+                cross(direction, next).atan2(direction.0 * next.0 + direction.1 * next.1)
+            };
+
+            let next = outgoing.get(&key(end)).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .filter(|i| !used[**i])
+                    .max_by(|i, j| turn(i).total_cmp(&turn(j)))
+            });
+
+            match next {
+                Some(&next) => {
+                    contour.push(end);
+                    used[next] = true;
+                    edge = next;
+                }
+                None => break false,
+            }
+        };
+
+        if closed && contour.len() > 2 {
+            contours.push(contour);
+        }
+    }
+
+    contours
+}
+
+fn min_x((from, to): (Point, Point)) -> f64 {
+    from.0.min(to.0)
+}
+
+fn max_x((from, to): (Point, Point)) -> f64 {
+    from.0.max(to.0)
+}
+
+/// Hashable bits of a point, `-0.0` being turned into `0.0` as `==` does.
+fn key((x, y): Point) -> (u64, u64) {
+    ((x + 0.0).to_bits(), (y + 0.0).to_bits())
+}
+
+fn cross(a: Point, b: Point) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::font::Font;
+    use crate::svg::layout::{Length, TextOptions};
+    use crate::svg::testing::{path, ARIAL_SVG_FONT};
+
+    #[test]
+    fn it_combines_polygons() {
+        let a = path("M0 0 H10 V10 H0 Z");
+        let b = path("M5 5 V15 H15 V5 Z");
+        let options = BooleanOptions::default();
+
+        let union = a.union(&b, &options);
+
+        assert_eq!(union.signed_area(), 175.0);
+        assert_eq!(union.subpaths().count(), 1);
+        assert!(union.contains((12.0, 12.0), FillRule::NonZero));
+        assert_eq!(a.intersection(&b, &options).signed_area(), 25.0);
+        assert_eq!(a.difference(&b, &options).signed_area(), 75.0);
+        assert_eq!(b.difference(&a, &options).signed_area(), 75.0);
+
+        let xor = a.xor(&b, &options);
+
+        assert_eq!(xor.signed_area(), 150.0);
+        assert!(!xor.contains((7.0, 7.0), FillRule::EvenOdd));
+        assert!(!xor.contains((7.0, 7.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn it_follows_the_fill_rules() {
+        let twice = path("M0 0 H10 V10 H0 Z M0 0 H10 V10 H0 Z");
+        let hole = path("M0 0 H30 V30 H0 Z M10 10 H20 V20 H10 Z");
+        let even_odd = BooleanOptions {
+            fill_rule: FillRule::EvenOdd,
+            ..BooleanOptions::default()
+        };
+
+        assert_eq!(
+            twice
+                .remove_overlaps(&BooleanOptions::default())
+                .signed_area(),
+            100.0
+        );
+        assert_eq!(twice.remove_overlaps(&even_odd).to_string(), "");
+        assert_eq!(
+            hole.remove_overlaps(&BooleanOptions::default())
+                .signed_area(),
+            900.0
+        );
+        assert_eq!(hole.remove_overlaps(&even_odd).signed_area(), 800.0);
+    }
+
+    #[test]
+    fn it_keeps_contours_touching_at_a_point_apart() {
+        let a = path("M0 0 H10 V10 H0 Z");
+        let b = path("M10 10 H20 V20 H10 Z");
+        let union = a.union(&b, &BooleanOptions::default());
+
+        assert_eq!(union.subpaths().count(), 2);
+        assert_eq!(
+            union
+                .subpaths()
+                .map(|subpath| subpath.signed_area())
+                .collect::<Vec<f64>>(),
+            vec![100.0, 100.0]
+        );
+    }
+
+    #[test]
+    fn it_links_vertices_at_zero_and_negative_zero() {
+        let a = path("M0 0 H10 V10 H-0 Z");
+        let b = path("M-0 10 H10 V20 H0 Z");
+        let union = a.union(&b, &BooleanOptions::default());
+
+        assert_eq!(union.signed_area(), 200.0);
+        assert_eq!(union.subpaths().count(), 1);
+        assert_eq!(
+            contours(&[
+                ((0.0, 0.0), (1.0, 0.0)),
+                ((1.0, 0.0), (-0.0, 1.0)),
+                ((0.0, 1.0), (-0.0, 0.0)),
+            ])
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn it_drops_chains_which_do_not_close() {
+        let edges = [
+            ((0.0, 0.0), (1.0, 0.0)),
+            ((1.0, 0.0), (1.0, 1.0)),
+            ((1.0, 1.0), (0.0, 0.0)),
+            ((5.0, 5.0), (6.0, 5.0)),
+            ((6.0, 5.0), (6.0, 6.0)),
+        ];
+
+        assert_eq!(
+            contours(&edges),
+            vec![vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]]
+        );
+    }
+
+    #[test]
+    fn it_combines_curves() {
+        let a = path("M0 0 A100 100 0 0 0 200 0 A100 100 0 0 0 0 0 Z");
+        let b = a.translate(100.0, 0.0);
+        let union = a.union(&b, &BooleanOptions::default());
+        let lens = 20000.0 * 0.5f64.acos() - 50.0 * 30000f64.sqrt();
+
+        assert!(union.to_string().contains('C'));
+        assert_eq!(union.subpaths().count(), 1);
+        assert!((union.signed_area() - (2.0 * std::f64::consts::PI * 10000.0 - lens)).abs() < 50.0);
+    }
+
+    #[test]
+    fn it_removes_overlaps_between_glyphs() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let options = TextOptions {
+            letter_spacing: Length::Absolute(-400.0),
+            ..TextOptions::default()
+        };
+        let text = font.text_path("HH", &options);
+        let outline = text.remove_overlaps(&BooleanOptions::default());
+
+        // The stems overlapping in the middle are merged.
+        assert_eq!(text.subpaths().count(), 2);
+        assert_eq!(outline.subpaths().count(), 1);
+        assert_eq!(
+            outline.signed_area(),
+            text.signed_area().abs() - 1466.0 * 72.0
+        );
+        assert!(outline.contains((1300.0, 100.0), FillRule::NonZero));
+    }
+}
@@ -0,0 +1,202 @@
+use super::path::{Path, Point, Segment};
+
+/// Builds a path segment by segment:
+/// `PathBuilder::new().move_to((0.0, 0.0)).line_to((10.0, 0.0)).build()`.
+#[derive(Debug, Clone, Default)]
+pub struct PathBuilder {
+    segments: Vec<Segment>,
+}
+
+impl PathBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn segment(mut self, segment: Segment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn move_to(self, to: Point) -> Self {
+        self.segment(Segment::MoveTo {
+            relative: false,
+            to,
+        })
+    }
+
+    pub fn relative_move_to(self, to: Point) -> Self {
+        self.segment(Segment::MoveTo { relative: true, to })
+    }
+
+    pub fn line_to(self, to: Point) -> Self {
+        self.segment(Segment::LineTo {
+            relative: false,
+            to,
+        })
+    }
+
+    pub fn relative_line_to(self, to: Point) -> Self {
+        self.segment(Segment::LineTo { relative: true, to })
+    }
+
+    pub fn quad_to(self, control: Point, to: Point) -> Self {
+        self.segment(Segment::QuadTo {
+            relative: false,
+            control,
+            to,
+        })
+    }
+
+    pub fn relative_quad_to(self, control: Point, to: Point) -> Self {
+        self.segment(Segment::QuadTo {
+            relative: true,
+            control,
+            to,
+        })
+    }
+
+    pub fn cubic_to(self, control1: Point, control2: Point, to: Point) -> Self {
+        self.segment(Segment::CubicTo {
+            relative: false,
+            control1,
+            control2,
+            to,
+        })
+    }
+
+    pub fn relative_cubic_to(self, control1: Point, control2: Point, to: Point) -> Self {
+        self.segment(Segment::CubicTo {
+            relative: true,
+            control1,
+            control2,
+            to,
+        })
+    }
+
+    pub fn arc_to(
+        self,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) -> Self {
+        self.segment(Segment::ArcTo {
+            relative: false,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+        })
+    }
+
+    pub fn relative_arc_to(
+        self,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) -> Self {
+        self.segment(Segment::ArcTo {
+            relative: true,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+        })
+    }
+
+    pub fn close(self) -> Self {
+        self.segment(Segment::Close)
+    }
+
+    /// Adds a closed rectangle, as a subpath of its own.
+    pub fn rect(self, x: f64, y: f64, width: f64, height: f64) -> Self {
+        self.move_to((x, y))
+            .line_to((x + width, y))
+            .line_to((x + width, y + height))
+            .line_to((x, y + height))
+            .close()
+    }
+
+    pub fn build(self) -> Path {
+        Path::from_segments(&self.segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_a_path() {
+        let path = PathBuilder::new()
+            .move_to((0.0, 0.0))
+            .relative_line_to((10.0, 0.0))
+            .quad_to((15.0, 5.0), (10.0, 10.0))
+            .relative_cubic_to((0.0, 5.0), (-5.0, 5.0), (-10.0, 0.0))
+            .arc_to((5.0, 5.0), 0.0, false, true, (0.0, 0.0))
+            .close()
+            .rect(20.0, -1.5, 100.0, 3.0)
+            .build();
+
+        assert_eq!(
+            path.to_string(),
+            "M0 0l10 0Q15 5 10 10c0 5 -5 5 -10 0A5 5 0 0 1 0 0Z\
+             M20 -1.5L120 -1.5L120 1.5L20 1.5Z"
+        );
+    }
+
+    #[test]
+    fn it_iterates_over_typed_segments() {
+        let path =
+            Path::new("M10 10 20 10 h5 V0 s5 5 10 0 T40 0 a5 5 0 1 0 10 0 z".to_string()).unwrap();
+        let segments = path.segments().collect::<Vec<Segment>>();
+
+        assert_eq!(
+            segments,
+            vec![
+                Segment::MoveTo {
+                    relative: false,
+                    to: (10.0, 10.0)
+                },
+                Segment::LineTo {
+                    relative: false,
+                    to: (20.0, 10.0)
+                },
+                Segment::LineTo {
+                    relative: true,
+                    to: (5.0, 0.0)
+                },
+                Segment::LineTo {
+                    relative: false,
+                    to: (25.0, 0.0)
+                },
+                Segment::CubicTo {
+                    relative: true,
+                    control1: (0.0, 0.0),
+                    control2: (5.0, 5.0),
+                    to: (10.0, 0.0)
+                },
+                Segment::QuadTo {
+                    relative: false,
+                    control: (35.0, 0.0),
+                    to: (40.0, 0.0)
+                },
+                Segment::ArcTo {
+                    relative: true,
+                    radii: (5.0, 5.0),
+                    x_axis_rotation: 0.0,
+                    large_arc: true,
+                    sweep: false,
+                    to: (10.0, 0.0)
+                },
+                Segment::Close,
+            ]
+        );
+        assert_eq!(Path::from_segments(&segments).elements(), path.elements());
+    }
+}
@@ -0,0 +1,283 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::bidi::{visual_runs, Direction};
+use super::font::Font;
+use super::layout::{Length, PositionedGlyph, TextOptions};
+use super::path::Path;
+
+/// Several fonts in priority order, each grapheme cluster being drawn with the
+/// first font able to draw it.
+///
+/// Measurements are expressed in the units of the first font, other fonts
+/// being scaled to its em size.
+pub struct FontCollection {
+    fonts: Vec<Font>,
+}
+
+/// Consecutive glyphs drawn with the same font.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontRun {
+    /// Index of the font in the collection.
+    pub font: usize,
+    /// Byte range of the run in the logical text.
+    pub range: Range<usize>,
+    /// Glyphs of the run, positioned and scaled in the collection units.
+    pub glyphs: Vec<PositionedGlyph>,
+}
+
+impl FontCollection {
+    pub fn new(fonts: Vec<Font>) -> Self {
+        FontCollection { fonts }
+    }
+
+    /// Adds a font with the lowest priority.
+    pub fn push(&mut self, font: Font) {
+        self.fonts.push(font);
+    }
+
+    pub fn fonts(&self) -> &[Font] {
+        &self.fonts
+    }
+
+    pub fn units_per_em(&self) -> f64 {
+        match self.fonts.first() {
+            Some(font) => font.units_per_em(),
+            None => 0.0,
+        }
+    }
+
+    /// Index of the first font drawing `grapheme`, the first font being used
+    /// when none can.
+    pub fn font_for(&self, grapheme: &str) -> usize {
+        self.fonts
+            .iter()
+            .position(|font| font.has_grapheme(grapheme))
+            .unwrap_or(0)
+    }
+
+    /// Splits `text`, in logical order, into ranges drawn by the same font.
+    pub fn font_ranges(&self, text: &str) -> Vec<(usize, Range<usize>)> {
+        let mut ranges: Vec<(usize, Range<usize>)> = Vec::new();
+
+        for (index, grapheme) in text.grapheme_indices(true) {
+            let font = self.font_for(grapheme);
+
+            match ranges.last_mut() {
+                Some((last, range)) if *last == font => range.end = index + grapheme.len(),
+                _ => ranges.push((font, index..index + grapheme.len())),
+            }
+        }
+
+        ranges
+    }
+
+    /// Lays out `text` like `Font::layout`, switching fonts where needed, and
+    /// returns the font runs in visual order.
+    pub fn layout(&self, text: &str, options: &TextOptions) -> Vec<FontRun> {
+        let mut runs: Vec<FontRun> = Vec::new();
+
+        if self.fonts.is_empty() {
+            return runs;
+        }
+
+        let ranges = self.font_ranges(text);
+        let mut x = 0.0;
+
+        for visual_run in visual_runs(text, options.direction) {
+            let mut pieces = ranges
+                .iter()
+                .filter_map(|(font, range)| {
+                    let start = range.start.max(visual_run.range.start);
+                    let end = range.end.min(visual_run.range.end);
+
+                    (start < end).then_some((*font, start..end))
+                })
+                .collect::<Vec<(usize, Range<usize>)>>();
+
+            if visual_run.rtl {
+                pieces.reverse();
+            }
+
+            for (font_index, range) in pieces {
+                let font = &self.fonts[font_index];
+                let scale = self.units_per_em() / font.units_per_em();
+
+                let piece_options = TextOptions {
+                    letter_spacing: unscale(options.letter_spacing, scale),
+                    word_spacing: unscale(options.word_spacing, scale),
+                    tab_width: options.tab_width.map(|tab_width| unscale(tab_width, scale)),
+                    trailing_spacing: true,
+                    direction: if visual_run.rtl {
+                        Direction::Rtl
+                    } else {
+                        Direction::Ltr
+                    },
+                    ..options.clone()
+                };
+
+                let glyphs = font
+                    .layout_from(&text[range.clone()], &piece_options, x / scale)
+                    .into_iter()
+                    .map(|glyph| PositionedGlyph {
+                        index: range.start + glyph.index,
+                        x: glyph.x * scale,
+                        advance: glyph.advance * scale,
+                        path: scale_path(glyph.path.clone(), scale),
+                        ..glyph
+                    })
+                    .collect::<Vec<PositionedGlyph>>();
+
+                if let Some(last) = glyphs.last() {
+                    x = last.x + last.advance;
+                }
+
+                runs.push(FontRun {
+                    font: font_index,
+                    range,
+                    glyphs,
+                });
+            }
+        }
+
+        if !options.trailing_spacing {
+            if let Some(run) = runs.last_mut() {
+                let font = &self.fonts[run.font];
+                let scale = self.units_per_em() / font.units_per_em();
+
+                if let Some(last) = run.glyphs.last_mut() {
+                    last.advance -= options.letter_spacing.resolve(font.units_per_em()) * scale;
+                }
+            }
+        }
+
+        runs
+    }
+
+    pub fn text_width(&self, text: &str, options: &TextOptions) -> f64 {
+        let runs = self.layout(text, options);
+        let glyphs = runs.iter().flat_map(|run| run.glyphs.iter());
+
+        let width = match glyphs.clone().last() {
+            Some(last) => last.x + last.advance,
+            None => 0.0,
+        };
+
+        let left_margin = glyphs
+            .filter(|glyph| !glyph.path.is_empty())
+            .find_map(|glyph| Path::new(glyph.path.clone()).ok())
+            .map_or(0.0, |path| path.bounds().unwrap().x1());
+
+        width - left_margin
+    }
+
+    /// Renders `text` into a single path, in the collection units.
+    pub fn text_path(&self, text: &str, options: &TextOptions) -> Path {
+        let mut path = Path::default();
+
+        for run in self.layout(text, options) {
+            for glyph in run.glyphs {
+                if glyph.path.is_empty() {
+                    continue;
+                }
+
+                if let Ok(glyph_path) = Path::new(glyph.path) {
+                    path.append(glyph_path.translate(glyph.x, 0.0));
+                }
+            }
+        }
+
+        path
+    }
+}
+
+/// Converts a length given in collection units into a font's own units.
+fn unscale(length: Length, scale: f64) -> Length {
+    match length {
+        Length::Absolute(value) => Length::Absolute(value / scale),
+        Length::Em(value) => Length::Em(value),
+    }
+}
+
+fn scale_path(path: String, scale: f64) -> String {
+    if path.is_empty() || scale == 1.0 {
+        return path;
+    }
+
+    match Path::new(path.clone()) {
+        Ok(parsed) => parsed.scale(scale).to_string(),
+        Err(_) => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    fn symbols_font() -> Font {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face units-per-em="1024" ascent="800" descent="-200" />
+                    <glyph unicode="&#x2605;" horiz-adv-x="1000" d="M0 0h1000v1000h-1000z" />
+                    <glyph unicode="a" horiz-adv-x="100" d="M0 0h100v100h-100z" />
+                </font>
+            </svg>"#;
+
+        Font::new(data.to_string()).unwrap()
+    }
+
+    #[test]
+    fn it_picks_the_first_font_having_the_glyph() {
+        let collection = FontCollection::new(vec![
+            Font::new(ARIAL_SVG_FONT.to_string()).unwrap(),
+            symbols_font(),
+        ]);
+
+        assert_eq!(
+            collection.font_ranges("ab\u{2605}\u{2605}c"),
+            vec![(0, 0..2), (1, 2..8), (0, 8..9)]
+        );
+    }
+
+    #[test]
+    fn it_scales_fallback_fonts_to_the_first_font_units() {
+        let collection = FontCollection::new(vec![
+            Font::new(ARIAL_SVG_FONT.to_string()).unwrap(),
+            symbols_font(),
+        ]);
+
+        let runs = collection.layout("a\u{2605}a", &TextOptions::default());
+        let a = collection.fonts()[0].layout("a", &TextOptions::default())[0].advance;
+
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[1].font, 1);
+        assert_eq!(runs[1].glyphs[0].x, a);
+        assert_eq!(runs[1].glyphs[0].advance, 2000.0);
+        assert_eq!(runs[1].glyphs[0].path, "M0 0h2000v2000h-2000z");
+        assert_eq!(runs[2].glyphs[0].x, a + 2000.0);
+        assert_eq!(
+            collection.text_width("a\u{2605}a", &TextOptions::default()),
+            collection.fonts()[0].text_width("aa".to_string()) + 2000.0
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_visual_order_across_fonts() {
+        let collection = FontCollection::new(vec![
+            symbols_font(),
+            Font::new(ARIAL_SVG_FONT.to_string()).unwrap(),
+        ]);
+
+        let runs = collection.layout("\u{5d0}\u{2605}\u{5d1}", &TextOptions::default());
+
+        let texts = runs
+            .iter()
+            .flat_map(|run| run.glyphs.iter().map(|g| g.text.clone()))
+            .collect::<String>();
+
+        assert_eq!(texts, "\u{5d1}\u{2605}\u{5d0}");
+    }
+}
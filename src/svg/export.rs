@@ -0,0 +1,345 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use super::font::{Font, Glyph};
+use super::layout::Orientation;
+use super::matching::FONT_STRETCHES;
+use super::path::round;
+use super::stream::{PathParser, SerializeSink};
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Decimals kept in numbers and path data, written as they are when `None`.
+    pub precision: Option<usize>,
+}
+
+impl Font {
+    /// Writes the font as an SVG font document which `Font::new` loads back.
+    pub fn to_svg(&self, options: &ExportOptions) -> String {
+        let number = |value: f64| number(value, options.precision);
+
+        let mut svg = String::new();
+
+        svg.push_str("<?xml version=\"1.0\" standalone=\"no\"?>\n");
+        svg.push_str("<svg xmlns=\"http://www.w3.org/2000/svg\">\n<defs>\n<font");
+
+        if let Some(id) = &self.id {
+            write!(svg, " id=\"{}\"", escape(id, false)).unwrap();
+        }
+
+        writeln!(
+            svg,
+            " horiz-adv-x=\"{}\">",
+            number(self.horizontal_advance_x)
+        )
+        .unwrap();
+
+        svg.push_str("  <font-face");
+
+        if let Some(family) = &self.family {
+            write!(svg, " font-family=\"{}\"", escape(family, false)).unwrap();
+        }
+
+        let stretch = match FONT_STRETCHES.iter().find(|(_, p)| *p == self.stretch) {
+            Some((keyword, _)) => keyword.to_string(),
+            None => format!("{}%", self.stretch),
+        };
+
+        write!(
+            svg,
+            " font-weight=\"{}\" font-style=\"{}\" font-stretch=\"{}\"",
+            self.weight,
+            self.style.as_str(),
+            stretch
+        )
+        .unwrap();
+        write!(
+            svg,
+            " units-per-em=\"{}\" ascent=\"{}\" descent=\"{}\"",
+            number(self.units_per_em),
+            number(self.ascent),
+            number(self.descent)
+        )
+        .unwrap();
+
+        for (name, value) in &self.font_face {
+            write!(svg, " {}=\"{}\"", name, escape(value, false)).unwrap();
+        }
+
+        svg.push_str(" />\n");
+
+        if let Some(glyph) = &self.missing_glyph {
+            svg.push_str("  <missing-glyph");
+            self.write_glyph_attributes(&mut svg, glyph, options);
+            svg.push_str(" />\n");
+        }
+
+        let names = self.glyph_names();
+
+        for (glyph, name) in self.glyphs.iter().zip(&names) {
+            svg.push_str("  <glyph");
+
+            if let Some(name) = name {
+                write!(svg, " glyph-name=\"{}\"", escape(name, false)).unwrap();
+            }
+
+            let unicode = glyph.characters.iter().collect::<String>();
+            write!(svg, " unicode=\"{}\"", escape(&unicode, true)).unwrap();
+
+            if let Some(form) = glyph.arabic_form {
+                write!(svg, " arabic-form=\"{}\"", form.as_str()).unwrap();
+            }
+
+            if !glyph.lang.is_empty() {
+                write!(svg, " lang=\"{}\"", escape(&glyph.lang.join(","), false)).unwrap();
+            }
+
+            match glyph.orientation {
+                Some(Orientation::Horizontal) => svg.push_str(" orientation=\"h\""),
+                Some(Orientation::Vertical) => svg.push_str(" orientation=\"v\""),
+                None => {}
+            }
+
+            self.write_glyph_attributes(&mut svg, glyph, options);
+            svg.push_str(" />\n");
+        }
+
+        let mut kerning = self
+            .kerning
+            .iter()
+            .collect::<Vec<(&(usize, usize), &f64)>>();
+        kerning.sort_by_key(|(pair, _)| **pair);
+
+        // Kerned glyphs all have a unique name, see `glyph_names`.
+        for ((first, second), k) in kerning {
+            writeln!(
+                svg,
+                "  <hkern g1=\"{}\" g2=\"{}\" k=\"{}\" />",
+                escape(names[*first].as_deref().unwrap(), false),
+                escape(names[*second].as_deref().unwrap(), false),
+                number(*k)
+            )
+            .unwrap();
+        }
+
+        svg.push_str("</font>\n</defs>\n</svg>\n");
+
+        svg
+    }
+
+    /// The names written for the glyphs. Kerned glyphs without a name of
+    /// their own, that is one which no other glyph has and kerning pairs can
+    /// refer to, are named after their id, such as `g12`.
+    fn glyph_names(&self) -> Vec<Option<String>> {
+        let mut counts = HashMap::new();
+
+        for name in self.glyphs.iter().filter_map(|g| g.name.as_deref()) {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+
+        let kerned = self
+            .kerning
+            .keys()
+            .flat_map(|(first, second)| [*first, *second])
+            .collect::<HashSet<usize>>();
+        let mut taken = counts
+            .keys()
+            .map(|name| name.to_string())
+            .collect::<HashSet<String>>();
+
+        self.glyphs
+            .iter()
+            .enumerate()
+            .map(|(id, glyph)| match glyph.name.as_deref() {
+                Some(name) if counts[name] == 1 && !name.contains(',') => Some(name.to_string()),
+                _ if kerned.contains(&id) => {
+                    let mut name = format!("g{}", id);
+
+                    while taken.contains(&name) {
+                        name.push('_');
+                    }
+
+                    taken.insert(name.clone());
+                    Some(name)
+                }
+                name => name.map(|name| name.to_string()),
+            })
+            .collect()
+    }
+
+    fn write_glyph_attributes(&self, svg: &mut String, glyph: &Glyph, options: &ExportOptions) {
+        if glyph.horizontal_advance_x != self.horizontal_advance_x {
+            write!(
+                svg,
+                " horiz-adv-x=\"{}\"",
+                number(glyph.horizontal_advance_x, options.precision)
+            )
+            .unwrap();
+        }
+
+        if glyph.path.is_empty() {
+            return;
+        }
+
+        // Rounded paths are written with absolute coordinates, so that the
+        // rounding errors of relative segments don't add up.
+        let path = match options.precision {
+            Some(precision) => {
+                let mut sink = SerializeSink::new(Some(precision));
+
+                match PathParser::new(&glyph.path).feed(&mut sink) {
+                    Ok(()) => sink.into_string(),
+                    Err(_) => glyph.path.clone(),
+                }
+            }
+            None => glyph.path.clone(),
+        };
+
+        write!(svg, " d=\"{}\"", escape(&path, false)).unwrap();
+    }
+}
+
+fn number(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => round(value, precision).to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Escapes an attribute value, and every non ASCII character when `ascii`.
+fn escape(value: &str, ascii: bool) -> String {
+    let mut escaped = String::new();
+
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_control() || (ascii && !c.is_ascii()) => {
+                write!(escaped, "&#x{:x};", c as u32).unwrap()
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::matching::FontStyle;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_exports_a_font_that_loads_back() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let exported = Font::new(font.to_svg(&ExportOptions::default())).unwrap();
+
+        assert_eq!(exported.id(), Some("MicrosoftSansSerif"));
+        assert_eq!(exported.family(), Some("Microsoft Sans Serif"));
+        assert_eq!(exported.units_per_em(), 2048.0);
+        assert_eq!(exported.missing_glyph_advance(), Some(600.0));
+        assert_eq!(exported.glyphs().len(), font.glyphs().len());
+        assert_eq!(exported.font_height(), 2270.0);
+        assert_eq!(
+            exported.text_width("Hello World".to_string()),
+            font.text_width("Hello World".to_string())
+        );
+        assert!(font
+            .to_svg(&ExportOptions::default())
+            .contains(" x-height=\"1061\""));
+    }
+
+    #[test]
+    fn it_exports_glyph_names_and_kerning() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face font-family="Kerned" font-style="italic" font-stretch="80%" units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph glyph-name="A" unicode="A" horiz-adv-x="600" d="M0 0L300 700L600 0Z" />
+                    <glyph unicode="&amp;" horiz-adv-x="600" />
+                    <glyph glyph-name="alef" unicode="&#x627;" arabic-form="isolated" lang="ar" />
+                    <hkern g1="A" u2="&amp;" k="80" />
+                </font>
+            </svg>"#;
+
+        let svg = Font::new(data.to_string())
+            .unwrap()
+            .to_svg(&ExportOptions::default());
+        let font = Font::new(svg.clone()).unwrap();
+
+        assert!(svg.contains(
+            r#"<glyph glyph-name="A" unicode="A" horiz-adv-x="600" d="M0 0L300 700L600 0Z" />"#
+        ));
+        assert!(svg.contains(r#"unicode="&#x627;" arabic-form="isolated" lang="ar""#));
+        assert!(svg.contains(r#"<glyph glyph-name="g1" unicode="&amp;" horiz-adv-x="600" />"#));
+        assert!(svg.contains(r#"<hkern g1="A" g2="g1" k="80" />"#));
+        assert_eq!(font.style(), FontStyle::Italic);
+        assert_eq!(font.stretch(), 80.0);
+        assert_eq!(font.kerning('A', '&'), -80.0);
+    }
+
+    #[test]
+    fn it_rounds_numbers_to_the_given_precision() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face units-per-em="1000" ascent="800.25" descent="-200" />
+                    <glyph unicode="a" horiz-adv-x="500.456" d="M0.123 -0.004L10.987 20.5Z" />
+                </font>
+            </svg>"#;
+
+        let font = Font::new(data.to_string()).unwrap();
+        let svg = font.to_svg(&ExportOptions { precision: Some(1) });
+
+        assert!(svg.contains(r#"ascent="800.3""#));
+        assert!(svg.contains(r#"horiz-adv-x="500.5" d="M0.1 0L11 20.5Z""#));
+        assert!(font
+            .to_svg(&ExportOptions::default())
+            .contains(r#"d="M0.123 -0.004L10.987 20.5Z""#));
+    }
+
+    #[test]
+    fn it_keeps_the_kerning_of_glyphs_sharing_a_unicode() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph glyph-name="a" unicode="a" />
+                    <glyph glyph-name="a" unicode="a" lang="tr" />
+                    <glyph unicode="V" />
+                    <glyph glyph-name="g2" unicode="W" />
+                    <hkern g1="a" u2="V" k="80" />
+                </font>
+            </svg>"#;
+
+        let mut font = Font::new(data.to_string()).unwrap();
+        font.kerning.remove(&(1, 2));
+
+        let svg = font.to_svg(&ExportOptions::default());
+        let exported = Font::new(svg.clone()).unwrap();
+
+        assert!(svg.contains(r#"<glyph glyph-name="g0" unicode="a" />"#));
+        assert!(svg.contains(r#"<glyph glyph-name="g2_" unicode="V" />"#));
+        assert!(svg.contains(r#"<hkern g1="g0" g2="g2_" k="80" />"#));
+        assert_eq!(exported.kerning, font.kerning);
+    }
+
+    #[test]
+    fn it_rounds_relative_segments_without_drifting() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="a" d="M0 0l0.4 0l0.4 0l0.4 0v10.6h-1.2z" />
+                </font>
+            </svg>"#;
+
+        let font = Font::new(data.to_string()).unwrap();
+        let svg = font.to_svg(&ExportOptions { precision: Some(0) });
+
+        assert!(svg.contains(r#"d="M0 0L0 0L1 0L1 0L1 11L0 11Z""#));
+    }
+}
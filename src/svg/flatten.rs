@@ -0,0 +1,250 @@
+use super::path::{arc_to_cubics, Path, Point};
+use super::stream::PathSink;
+
+/// The points of a subpath joined by straight lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    /// Whether the subpath ends with a closepath, the last point then being
+    /// joined to the first one, which isn't repeated.
+    pub is_closed: bool,
+}
+
+/// Subdivisions of a single curve, enough for any reasonable tolerance.
+const MAX_DEPTH: usize = 16;
+
+impl Path {
+    /// Converts every subpath to a polyline which stays within `tolerance` of
+    /// the path. Subpaths without any segment are left out.
+    pub fn flatten(&self, tolerance: f64) -> impl Iterator<Item = Polyline> {
+        let mut sink = FlattenSink::new(tolerance);
+        self.feed(&mut sink);
+
+        sink.into_polylines().into_iter()
+    }
+}
+
+/// Flattens the segments it receives to polylines, see `Path::flatten`.
+#[derive(Debug, Clone)]
+pub struct FlattenSink {
+    tolerance: f64,
+    polylines: Vec<Polyline>,
+    polyline: Option<Polyline>,
+}
+
+impl FlattenSink {
+    pub fn new(tolerance: f64) -> Self {
+        FlattenSink {
+            // Half of the tolerance goes to arcs approximated with cubic curves.
+            tolerance: tolerance.abs().max(f64::EPSILON) / 2.0,
+            polylines: Vec::new(),
+            polyline: None,
+        }
+    }
+
+    /// The polylines of the subpaths having segments.
+    pub fn into_polylines(mut self) -> Vec<Polyline> {
+        self.polylines.extend(self.polyline.take());
+        self.polylines.retain(|polyline| polyline.points.len() > 1);
+        self.polylines
+    }
+}
+
+impl PathSink for FlattenSink {
+    fn move_to(&mut self, to: Point) {
+        self.polylines.extend(self.polyline.take());
+        self.polyline = Some(Polyline {
+            points: vec![to],
+            is_closed: false,
+        });
+    }
+
+    fn line_to(&mut self, to: Point) {
+        if let Some(polyline) = self.polyline.as_mut() {
+            polyline.points.push(to);
+        }
+    }
+
+    fn quad_to(&mut self, control: Point, to: Point) {
+        if let Some(polyline) = self.polyline.as_mut() {
+            let current = polyline.points[polyline.points.len() - 1];
+            // Degree elevation gives the same curve as a cubic.
+            let c1 = lerp(current, control, 2.0 / 3.0);
+            let c2 = lerp(to, control, 2.0 / 3.0);
+
+            flatten_cubic(current, c1, c2, to, self.tolerance, 0, &mut polyline.points);
+        }
+    }
+
+    fn cubic_to(&mut self, control1: Point, control2: Point, to: Point) {
+        if let Some(polyline) = self.polyline.as_mut() {
+            let current = polyline.points[polyline.points.len() - 1];
+
+            flatten_cubic(
+                current,
+                control1,
+                control2,
+                to,
+                self.tolerance,
+                0,
+                &mut polyline.points,
+            );
+        }
+    }
+
+    fn arc_to(
+        &mut self,
+        from: Point,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) {
+        if radii.0 == 0.0 || radii.1 == 0.0 {
+            self.line_to(to);
+            return;
+        }
+
+        for (c1, c2, to) in arc_to_cubics(
+            from,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+            self.tolerance,
+        ) {
+            self.cubic_to(c1, c2, to);
+        }
+    }
+
+    fn close(&mut self) {
+        let mut polyline = match self.polyline.take() {
+            Some(polyline) => polyline,
+            None => return,
+        };
+        let start = polyline.points[0];
+
+        if polyline.points.len() > 1 && polyline.points.last() == Some(&start) {
+            polyline.points.pop();
+        }
+
+        polyline.is_closed = true;
+        self.polylines.push(polyline);
+
+        // Drawing after a closepath starts again from its start.
+        self.polyline = Some(Polyline {
+            points: vec![start],
+            is_closed: false,
+        });
+    }
+}
+
+/// Pushes the points of a cubic curve, after its start, subdividing it until
+/// its control points are close enough to the chord.
+fn flatten_cubic(
+    p0: Point,
+    c1: Point,
+    c2: Point,
+    p3: Point,
+    tolerance: f64,
+    depth: usize,
+    points: &mut Vec<Point>,
+) {
+    // The curve is within 3/4 of its largest second difference from the chord.
+    let flatness = 0.75
+        * (p0.0 - 2.0 * c1.0 + c2.0)
+            .hypot(p0.1 - 2.0 * c1.1 + c2.1)
+            .max((c1.0 - 2.0 * c2.0 + p3.0).hypot(c1.1 - 2.0 * c2.1 + p3.1));
+
+    if flatness <= tolerance || depth >= MAX_DEPTH {
+        points.push(p3);
+        return;
+    }
+
+    let (a, b, c) = (lerp(p0, c1, 0.5), lerp(c1, c2, 0.5), lerp(c2, p3, 0.5));
+    let (d, e) = (lerp(a, b, 0.5), lerp(b, c, 0.5));
+    let middle = lerp(d, e, 0.5);
+
+    flatten_cubic(p0, a, d, middle, tolerance, depth + 1, points);
+    flatten_cubic(middle, e, c, p3, tolerance, depth + 1, points);
+}
+
+pub(crate) fn lerp(a: Point, b: Point, t: f64) -> Point {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_flattens_lines_per_subpath() {
+        let path = Path::new("M0 0 H10 V10 z m20 0 l5 5 5 -5 M50 50".to_string()).unwrap();
+        let polylines = path.flatten(0.1).collect::<Vec<Polyline>>();
+
+        assert_eq!(
+            polylines,
+            vec![
+                Polyline {
+                    points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+                    is_closed: true,
+                },
+                Polyline {
+                    points: vec![(20.0, 0.0), (25.0, 5.0), (30.0, 0.0)],
+                    is_closed: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_flattens_arcs_within_the_tolerance() {
+        let path =
+            Path::new("M0 100 A100 100 0 1 1 200 100 A100 100 0 1 1 0 100 Z".to_string()).unwrap();
+
+        for tolerance in [1.0, 0.01] {
+            let polyline = path.flatten(tolerance).next().unwrap();
+            let distance = |(x, y): Point| (x - 100.0).hypot(y - 100.0);
+
+            assert!(polyline.is_closed);
+            assert_ne!(polyline.points.first(), polyline.points.last());
+
+            for (i, point) in polyline.points.iter().enumerate() {
+                let next = polyline.points[(i + 1) % polyline.points.len()];
+
+                assert!((distance(*point) - 100.0).abs() <= tolerance);
+                assert!(distance(lerp(*point, next, 0.5)) >= 100.0 - tolerance);
+            }
+        }
+
+        assert!(
+            path.flatten(0.01).next().unwrap().points.len()
+                > path.flatten(1.0).next().unwrap().points.len()
+        );
+    }
+
+    #[test]
+    fn it_flattens_smooth_curves_within_the_tolerance() {
+        let path = Path::new(
+            "M0 0 Q 50 100 100 0 T 200 0 C 250 100 300 -100 350 0 S 450 100 400 0".to_string(),
+        )
+        .unwrap();
+        let polyline = path.flatten(0.5).next().unwrap();
+
+        assert!(!polyline.is_closed);
+        assert_eq!(polyline.points.first(), Some(&(0.0, 0.0)));
+        assert_eq!(polyline.points.last(), Some(&(400.0, 0.0)));
+
+        // The smooth quadratic curve is the reflection of the first one.
+        let lowest = polyline
+            .points
+            .iter()
+            .filter(|(x, _)| *x > 100.0 && *x < 200.0)
+            .map(|(_, y)| *y)
+            .fold(0.0, f64::min);
+
+        assert!((lowest + 50.0).abs() <= 0.5);
+    }
+}
@@ -1,9 +1,14 @@
 use std::char::from_u32;
+use std::collections::HashMap;
 
-use html_escape::decode_html_entities;
 use roxmltree::*;
+use unicode_normalization::UnicodeNormalization;
 
+use super::arabic::{presentation_form, ArabicForm};
+use super::layout::{Orientation, TextOptions};
+use super::matching::{parse_font_stretch, parse_font_weight, FontStyle};
 use super::path::Path;
+use super::stream::{BoundsSink, PathParser};
 
 const VALID_CHARS: [char; 94] = [
     'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
@@ -13,29 +18,135 @@ const VALID_CHARS: [char; 94] = [
     'Ô', 'Ö', 'ô', 'ö', 'Ù', 'Û', 'Ü', 'ù', 'û', 'ü', 'Ç', 'ç', 'Œ', 'œ', 'Æ', 'æ', 'ß', 'µ',
 ];
 
-struct Glyph {
-    unicode: u32,
-    horizontal_advance_x: f64,
-    path: String,
+/// The `font-face` attributes the font model stores in its own fields.
+const FONT_FACE_ATTRIBUTES: [&str; 7] = [
+    "font-family",
+    "font-weight",
+    "font-style",
+    "font-stretch",
+    "units-per-em",
+    "ascent",
+    "descent",
+];
+
+#[derive(Clone)]
+pub(crate) struct Glyph {
+    /// Position of the glyph in the font, which kerning pairs refer to.
+    pub(crate) id: usize,
+    pub(crate) name: Option<String>,
+    pub(crate) unicode: u32,
+    /// Every character of the `unicode` attribute, more than one for ligatures.
+    pub(crate) characters: Vec<char>,
+    pub(crate) arabic_form: Option<ArabicForm>,
+    /// Languages the glyph is restricted to, any language when empty.
+    pub(crate) lang: Vec<String>,
+    pub(crate) orientation: Option<Orientation>,
+    pub(crate) horizontal_advance_x: f64,
+    pub(crate) path: String,
+}
+
+impl Glyph {
+    /// Tells whether the glyph may be used for text in the `lang` language and
+    /// `orientation`, following the SVG Fonts glyph selection rules.
+    pub(crate) fn matches(&self, lang: Option<&str>, orientation: Orientation) -> bool {
+        if let Some(glyph_orientation) = self.orientation {
+            if glyph_orientation != orientation {
+                return false;
+            }
+        }
+
+        if self.lang.is_empty() {
+            return true;
+        }
+
+        let lang = match lang {
+            Some(lang) => lang.to_lowercase(),
+            None => return false,
+        };
+
+        self.lang.iter().any(|glyph_lang| {
+            let glyph_lang = glyph_lang.to_lowercase();
+
+            glyph_lang == lang || glyph_lang.starts_with(&format!("{}-", lang))
+        })
+    }
 }
 
 pub struct Font {
-    horizontal_advance_x: f64,
-    units_per_em: f64,
-    ascent: f64,
-    descent: f64,
-    glyphs: Vec<Glyph>,
+    pub(crate) id: Option<String>,
+    pub(crate) horizontal_advance_x: f64,
+    pub(crate) units_per_em: f64,
+    pub(crate) ascent: f64,
+    pub(crate) descent: f64,
+    pub(crate) family: Option<String>,
+    pub(crate) weight: f64,
+    pub(crate) style: FontStyle,
+    pub(crate) stretch: f64,
+    /// Other `font-face` attributes, such as `x-height` or `panose-1`.
+    pub(crate) font_face: Vec<(String, String)>,
+    pub(crate) glyphs: Vec<Glyph>,
+    pub(crate) missing_glyph: Option<Glyph>,
+    /// Kerning between glyph ids, as SVG `k` values: positive values move
+    /// the glyphs closer.
+    pub(crate) kerning: HashMap<(usize, usize), f64>,
 }
 
 impl Font {
     pub fn new(svg: String) -> Result<Self, String> {
-        let doc = Document::parse(svg.as_str()).unwrap();
+        let doc = parse_document(&svg)?;
 
         let font_element = match doc.descendants().find(|n| n.has_tag_name("font")) {
             Some(n) => n,
             None => return Err("No font element found".to_string()),
         };
 
+        Font::from_element(font_element)
+    }
+
+    /// Loads every `<font>` element of the document, in document order.
+    pub fn load_all(svg: String) -> Result<Vec<Self>, String> {
+        let doc = parse_document(&svg)?;
+
+        let fonts = doc
+            .descendants()
+            .filter(|n| n.has_tag_name("font"))
+            .map(Font::from_element)
+            .collect::<Result<Vec<Font>, String>>()?;
+
+        if fonts.is_empty() {
+            return Err("No font element found".to_string());
+        }
+
+        Ok(fonts)
+    }
+
+    /// Loads the `<font>` element having the given `id`.
+    pub fn load_by_id(svg: String, id: &str) -> Result<Self, String> {
+        match Font::load_all(svg)?
+            .into_iter()
+            .find(|f| f.id() == Some(id))
+        {
+            Some(font) => Ok(font),
+            None => Err(format!("No font element found with id {}", id)),
+        }
+    }
+
+    /// Loads the first `<font>` element whose font-face has the given family.
+    pub fn load_by_family(svg: String, family: &str) -> Result<Self, String> {
+        let font = Font::load_all(svg)?.into_iter().find(|f| {
+            f.family()
+                .is_some_and(|font_family| font_family.eq_ignore_ascii_case(family))
+        });
+
+        match font {
+            Some(font) => Ok(font),
+            None => Err(format!("No font element found for family {}", family)),
+        }
+    }
+
+    fn from_element(font_element: Node) -> Result<Self, String> {
+        let id = font_element.attribute("id").map(|id| id.to_string());
+
         let font_face_element = match font_element
             .descendants()
             .find(|n| n.has_tag_name("font-face"))
@@ -64,34 +175,54 @@ impl Font {
             None => return Err("No descent attribute found".to_string()),
         };
 
+        let family = font_face_element
+            .attribute("font-family")
+            .map(|family| family.trim().to_string());
+
+        // Font faces may list several values, matching uses the first one.
+        let first_value = |name: &str| {
+            font_face_element
+                .attribute(name)
+                .and_then(|value| value.split(',').next())
+                .map(|value| value.trim())
+        };
+
+        let weight = first_value("font-weight")
+            .and_then(parse_font_weight)
+            .unwrap_or(400.0);
+        let style = first_value("font-style")
+            .and_then(FontStyle::parse)
+            .unwrap_or_default();
+        let stretch = first_value("font-stretch")
+            .and_then(parse_font_stretch)
+            .unwrap_or(100.0);
+
+        let font_face = font_face_element
+            .attributes()
+            .iter()
+            .filter(|a| a.namespace().is_none() && !FONT_FACE_ATTRIBUTES.contains(&a.name()))
+            .map(|a| (a.name().to_string(), a.value().to_string()))
+            .collect::<Vec<(String, String)>>();
+
         let glyphs = font_element
             .descendants()
             .filter(|n| {
                 if !n.has_tag_name("glyph") {
                     return false;
                 }
-                
-                if !n.attribute("unicode").is_some() {
-                    return false;
-                }
-
-                if !n.attribute("d").is_some() {
-                    return false;
-                }
 
-                if VALID_CHARS.contains(&n.attribute("unicode").unwrap().chars().next().unwrap()) {
-                    return true;
+                match n.attribute("unicode") {
+                    Some(unicode) => !unicode.is_empty(),
+                    None => false,
                 }
-
-                false
             })
-            .map(|n| {
-                let unicode = n.attribute("unicode").unwrap().parse::<String>().unwrap();
+            .enumerate()
+            .map(|(id, n)| {
+                let unicode = n.attribute("unicode").unwrap();
 
-                let character = html_escape::decode_html_entities(&unicode)
+                let characters = html_escape::decode_html_entities(unicode)
                     .chars()
-                    .next()
-                    .unwrap();
+                    .collect::<Vec<char>>();
 
                 let horizontal_advance_x = match n.attribute("horiz-adv-x") {
                     Some(n) => n.parse::<f64>().unwrap(),
@@ -104,32 +235,140 @@ impl Font {
                 };
 
                 Glyph {
-                    unicode: character as u32,
-                    horizontal_advance_x: horizontal_advance_x,
-                    path: String::from(path),
+                    id,
+                    name: n.attribute("glyph-name").map(|name| name.to_string()),
+                    unicode: characters[0] as u32,
+                    characters,
+                    arabic_form: n.attribute("arabic-form").and_then(ArabicForm::parse),
+                    lang: match n.attribute("lang") {
+                        Some(lang) => lang
+                            .split(',')
+                            .map(|l| l.trim().to_string())
+                            .filter(|l| !l.is_empty())
+                            .collect(),
+                        None => Vec::new(),
+                    },
+                    orientation: match n.attribute("orientation") {
+                        Some("h") => Some(Orientation::Horizontal),
+                        Some("v") => Some(Orientation::Vertical),
+                        _ => None,
+                    },
+                    horizontal_advance_x,
+                    path,
                 }
             })
             .collect::<Vec<Glyph>>();
 
+        let missing_glyph = font_element
+            .descendants()
+            .find(|n| n.has_tag_name("missing-glyph"))
+            .map(|n| Glyph {
+                id: glyphs.len(),
+                name: None,
+                unicode: 0,
+                characters: Vec::new(),
+                arabic_form: None,
+                lang: Vec::new(),
+                orientation: None,
+                horizontal_advance_x: match n.attribute("horiz-adv-x") {
+                    Some(n) => n.parse::<f64>().unwrap(),
+                    None => horizontal_advance_x,
+                },
+                path: n.attribute("d").unwrap_or_default().to_string(),
+            });
+
+        let mut kerning = HashMap::new();
+
+        for hkern in font_element
+            .descendants()
+            .filter(|n| n.has_tag_name("hkern"))
+        {
+            let k = match hkern.attribute("k") {
+                Some(k) => k
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid hkern k attribute: {}", k))?,
+                None => return Err("No k attribute found on hkern".to_string()),
+            };
+
+            let firsts = kerning_glyphs(&glyphs, hkern.attribute("u1"), hkern.attribute("g1"));
+            let seconds = kerning_glyphs(&glyphs, hkern.attribute("u2"), hkern.attribute("g2"));
+
+            // The first kerning pair given for two glyphs wins.
+            for first in &firsts {
+                for second in &seconds {
+                    kerning.entry((*first, *second)).or_insert(k);
+                }
+            }
+        }
+
         Ok(Font {
+            id,
             horizontal_advance_x,
             units_per_em,
             ascent,
             descent,
+            family,
+            weight,
+            style,
+            stretch,
+            font_face,
             glyphs,
+            missing_glyph,
+            kerning,
         })
     }
 
+    /// The `id` attribute of the `<font>` element.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn horizontal_advance_x(&self) -> f64 {
+        self.horizontal_advance_x
+    }
+
+    pub fn units_per_em(&self) -> f64 {
+        self.units_per_em
+    }
+
+    pub fn ascent(&self) -> f64 {
+        self.ascent
+    }
+
+    pub fn descent(&self) -> f64 {
+        self.descent
+    }
+
+    pub fn family(&self) -> Option<&str> {
+        self.family.as_deref()
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn style(&self) -> FontStyle {
+        self.style
+    }
+
+    /// Width as a percentage of the normal width.
+    pub fn stretch(&self) -> f64 {
+        self.stretch
+    }
+
     pub fn font_height(&self) -> f64 {
         let mut min: f64 = 0.0;
         let mut max: f64 = 0.0;
 
-        for glyph in &self.glyphs {
-            if let Ok(path) = Path::new(glyph.path.clone()) {
-                let bounds = path.bounds().unwrap();
+        for glyph in self.valid_glyphs() {
+            let mut sink = BoundsSink::new();
 
-                min = min.min(bounds.y1()).min(bounds.y2());
-                max = max.max(bounds.y1()).max(bounds.y2());
+            if PathParser::new(&glyph.path).feed(&mut sink).is_ok() {
+                if let Some(bounds) = sink.into_bounds() {
+                    min = min.min(bounds.y1()).min(bounds.y2());
+                    max = max.max(bounds.y1()).max(bounds.y2());
+                }
             }
         }
 
@@ -140,56 +379,43 @@ impl Font {
         let mut min: f64 = 0.0;
         let mut max: f64 = 0.0;
 
-        for character in text.chars() {
-            let glyph = self.glyphs.iter().find(|g| g.unicode == character as u32);
+        for glyph in self.layout(&text, &TextOptions::default()) {
+            if glyph.path.is_empty() {
+                continue;
+            }
 
-            if let Some(glyph) = glyph {
-                if let Ok(path) = Path::new(glyph.path.clone()) {
-                    let bounds = path.bounds().unwrap();
+            if let Ok(path) = Path::new(glyph.path) {
+                let bounds = path.bounds().unwrap();
 
-                    min = min.min(bounds.y1()).min(bounds.y2());
-                    max = max.max(bounds.y1()).max(bounds.y2());
-                }
+                min = min.min(bounds.y1()).min(bounds.y2());
+                max = max.max(bounds.y1()).max(bounds.y2());
             }
         }
 
         max - min
     }
 
+    /// Every glyph with a `unicode` attribute advances the text, including
+    /// those without an outline such as the space and characters outside of
+    /// the letters `font_height` measures.
     pub fn text_width(&self, text: String) -> f64 {
-        let mut width: f64 = 0.0;
-
-        for character in text.chars() {
-            let glyph = self.glyphs.iter().find(|g| g.unicode == character as u32);
-
-            if let Some(glyph) = glyph {
-                width += glyph.horizontal_advance_x;
-            }
-        }
-
-        width - self.left_margin_for_text(text)
+        self.text_width_with(&text, &TextOptions::default())
     }
 
     pub fn highest_glyph(&self) -> char {
-        let mut highest_glyph = Glyph {
-            unicode: self.glyphs[0].unicode,
-            horizontal_advance_x: self.glyphs[0].horizontal_advance_x,
-            path: String::from(self.glyphs[0].path.clone()),
-        };
+        let mut highest_glyph = self.valid_glyphs().next().unwrap();
 
         let path = Path::new(highest_glyph.path.clone()).unwrap();
 
         let mut highest_y = path.bounds().unwrap().y1();
 
-        for glyph in &self.glyphs {
+        for glyph in self.valid_glyphs() {
             if let Ok(path) = Path::new(glyph.path.clone()) {
                 let bounds = path.bounds().unwrap();
 
                 if bounds.y1() < highest_y {
                     highest_y = bounds.y1();
-                    highest_glyph.unicode = glyph.unicode;
-                    highest_glyph.horizontal_advance_x = glyph.horizontal_advance_x;
-                    highest_glyph.path = glyph.path.clone();
+                    highest_glyph = glyph;
                 }
             }
         }
@@ -197,28 +423,167 @@ impl Font {
         from_u32(highest_glyph.unicode).unwrap()
     }
 
-    fn left_margin_for_text(&self, text: String) -> f64 {
-        for character in text.chars() {
-            let glyph = self.glyphs.iter().find(|g| g.unicode == character as u32);
+    /// Advance of the glyph drawn for characters the font has no glyph for.
+    pub fn missing_glyph_advance(&self) -> Option<f64> {
+        self.missing_glyph
+            .as_ref()
+            .map(|glyph| glyph.horizontal_advance_x)
+    }
 
-            if let Some(glyph) = glyph {
-                if let Ok(path) = Path::new(glyph.path.clone()) {
-                    let bounds = path.bounds().unwrap();
+    /// The adjustment, in font units, added to the advance of `left` when it
+    /// is followed by `right`. Negative values move the glyphs closer.
+    pub fn kerning(&self, left: char, right: char) -> f64 {
+        match (self.glyph_for(left), self.glyph_for(right)) {
+            (Some(left), Some(right)) => -self.kerning_between(left, right),
+            _ => 0.0,
+        }
+    }
 
-                    return bounds.x1();
-                }
-            }
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.glyph_for(character).is_some()
+    }
+
+    /// Tells whether the font can draw a whole grapheme cluster, either from
+    /// its precomposed or decomposed form. Invisible joiners and variation
+    /// selectors don't need a glyph.
+    pub fn has_grapheme(&self, grapheme: &str) -> bool {
+        let covers = |c: char| is_default_ignorable(c) || self.has_glyph(c);
+
+        grapheme.nfc().all(covers) || grapheme.nfd().all(covers)
+    }
+
+    pub(crate) fn glyphs(&self) -> &[Glyph] {
+        &self.glyphs
+    }
+
+    pub(crate) fn glyph_for(&self, character: char) -> Option<&Glyph> {
+        self.glyph_for_form(character, None, None, Orientation::Horizontal)
+    }
+
+    /// Finds the first glyph, in document order, for `character` usable in the
+    /// given contextual form, language and orientation. Characters outside of
+    /// any joining context use isolated glyphs.
+    pub(crate) fn glyph_for_form(
+        &self,
+        character: char,
+        form: Option<ArabicForm>,
+        lang: Option<&str>,
+        orientation: Orientation,
+    ) -> Option<&Glyph> {
+        let form = form.unwrap_or(ArabicForm::Isolated);
+        let glyphs = self.glyphs.iter().filter(|g| g.matches(lang, orientation));
+
+        let glyph = glyphs.clone().find(|g| {
+            g.characters == [character] && (g.arabic_form.is_none() || g.arabic_form == Some(form))
+        });
+
+        if glyph.is_some() {
+            return glyph;
+        }
+
+        let glyph = match presentation_form(character, form) {
+            Some(presentation) => glyphs.clone().find(|g| g.characters == [presentation]),
+            None => None,
+        };
+
+        // A glyph in another form still beats no glyph at all.
+        glyph.or_else(|| glyphs.clone().find(|g| g.characters == [character]))
+    }
+
+    /// The SVG `k` value between two glyphs of the font.
+    pub(crate) fn kerning_between(&self, left: &Glyph, right: &Glyph) -> f64 {
+        match self.kerning.get(&(left.id, right.id)) {
+            Some(k) => *k,
+            None => 0.0,
         }
+    }
+
+    /// The glyphs `font_height` and `highest_glyph` measure: single letters
+    /// and digits with an outline, leaving out punctuation and symbols which
+    /// may reach beyond the text lines.
+    fn valid_glyphs(&self) -> impl Iterator<Item = &Glyph> {
+        self.glyphs.iter().filter(|g| match from_u32(g.unicode) {
+            Some(character) => {
+                g.characters.len() == 1 && VALID_CHARS.contains(&character) && !g.path.is_empty()
+            }
+            None => false,
+        })
+    }
+}
 
-        0.0
+fn parse_document(svg: &str) -> Result<Document<'_>, String> {
+    match Document::parse(svg) {
+        Ok(doc) => Ok(doc),
+        Err(error) => Err(format!("Invalid SVG document: {}", error)),
     }
 }
 
+/// Ids of the glyphs selected by the `u` and `g` attributes of a kerning
+/// element: comma separated characters or unicode ranges, and glyph names.
+fn kerning_glyphs(glyphs: &[Glyph], unicodes: Option<&str>, names: Option<&str>) -> Vec<usize> {
+    let unicodes = unicodes
+        .map(|u| html_escape::decode_html_entities(u).to_string())
+        .unwrap_or_default();
+    let unicodes = unicodes
+        .split(',')
+        .map(|u| u.trim())
+        .filter(|u| !u.is_empty())
+        .collect::<Vec<&str>>();
+    let names = names
+        .unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<&str>>();
+
+    glyphs
+        .iter()
+        .filter(|glyph| {
+            let by_name = match &glyph.name {
+                Some(name) => names.contains(&name.as_str()),
+                None => false,
+            };
+
+            by_name
+                || unicodes.iter().any(|u| match parse_unicode_range(u) {
+                    Some(range) => {
+                        glyph.characters.len() == 1 && range.contains(&(glyph.characters[0] as u32))
+                    }
+                    None => glyph.characters.iter().copied().eq(u.chars()),
+                })
+        })
+        .map(|glyph| glyph.id)
+        .collect()
+}
+
+/// Parses a CSS unicode range such as `U+0041`, `U+0041-005A` or `U+00??`.
+fn parse_unicode_range(range: &str) -> Option<std::ops::RangeInclusive<u32>> {
+    let range = range
+        .strip_prefix("U+")
+        .or_else(|| range.strip_prefix("u+"))?;
+
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (start.to_string(), end.to_string()),
+        None => (range.replace('?', "0"), range.replace('?', "F")),
+    };
+
+    let start = u32::from_str_radix(&start, 16).ok()?;
+    let end = u32::from_str_radix(&end, 16).ok()?;
+
+    Some(start..=end)
+}
+
+fn is_default_ignorable(character: char) -> bool {
+    matches!(
+        character,
+        '\u{200c}' | '\u{200d}' | '\u{fe00}'..='\u{fe0f}' | '\u{e0100}'..='\u{e01ef}'
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const ARIAL_SVG_FONT: &'static str = include_str!("../../fixtures/arial.svg");
+    use crate::svg::testing::ARIAL_SVG_FONT;
 
     #[test]
     fn it_loads_font() {
@@ -272,10 +637,86 @@ mod tests {
         assert_eq!(font.text_width("a".to_string()), 1061.0);
     }
 
+    #[test]
+    fn it_advances_over_glyphs_without_an_outline_or_outside_of_letters() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let width = font.text_width("ab".to_string());
+
+        assert_eq!(font.text_width("a b".to_string()), width + 544.0);
+        assert_eq!(font.text_width("a,b".to_string()), width + 569.0);
+        assert_eq!(font.text_width("a-b".to_string()), width + 682.0);
+        assert_eq!(font.font_height(), 2270.0);
+    }
+
     #[test]
     fn it_finds_the_highest_arial_font_glyph() {
         let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
 
         assert_eq!(font.highest_glyph().to_string(), "g");
     }
+
+    #[test]
+    fn it_loads_every_font_of_a_document() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <defs>
+                    <font id="regular" horiz-adv-x="1000">
+                        <font-face font-family="Family" font-weight="400" units-per-em="1000" ascent="800" descent="-200" />
+                        <glyph unicode="a" horiz-adv-x="500" />
+                    </font>
+                    <font id="bold" horiz-adv-x="1000">
+                        <font-face font-family="Family" font-weight="700" units-per-em="1000" ascent="800" descent="-200" />
+                        <glyph unicode="a" horiz-adv-x="600" />
+                    </font>
+                    <font id="other" horiz-adv-x="1000">
+                        <font-face font-family="Other" units-per-em="2048" ascent="800" descent="-200" />
+                    </font>
+                </defs>
+            </svg>"#;
+
+        let fonts = Font::load_all(data.to_string()).unwrap();
+
+        assert_eq!(fonts.len(), 3);
+        assert_eq!(fonts[0].id(), Some("regular"));
+        assert_eq!(fonts[1].glyphs[0].horizontal_advance_x, 600.0);
+
+        let bold = Font::load_by_id(data.to_string(), "bold").unwrap();
+        let other = Font::load_by_family(data.to_string(), "other").unwrap();
+
+        assert_eq!(bold.weight(), 700.0);
+        assert_eq!(other.units_per_em(), 2048.0);
+        assert!(Font::load_by_id(data.to_string(), "italic").is_err());
+    }
+
+    #[test]
+    fn it_fails_on_invalid_documents() {
+        assert!(Font::new("<svg>".to_string()).is_err());
+        assert!(Font::load_all("<svg></svg>".to_string()).is_err());
+    }
+
+    #[test]
+    fn it_loads_kerning_pairs_and_the_missing_glyph() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <missing-glyph horiz-adv-x="800" d="M0 0h800v700h-800z" />
+                    <glyph glyph-name="A" unicode="A" horiz-adv-x="600" />
+                    <glyph glyph-name="V" unicode="V" horiz-adv-x="600" />
+                    <glyph glyph-name="W" unicode="W" horiz-adv-x="900" />
+                    <hkern u1="A" g2="V,W" k="80" />
+                    <hkern u1="U+0056-0057" u2="A" k="40" />
+                    <hkern u1="A" u2="V" k="10" />
+                </font>
+            </svg>"#;
+
+        let font = Font::new(data.to_string()).unwrap();
+
+        assert_eq!(font.missing_glyph_advance(), Some(800.0));
+        assert_eq!(font.kerning('A', 'V'), -80.0);
+        assert_eq!(font.kerning('A', 'W'), -80.0);
+        assert_eq!(font.kerning('W', 'A'), -40.0);
+        assert_eq!(font.kerning('V', 'V'), 0.0);
+        assert_eq!(font.text_width("VAV".to_string()), 1680.0);
+    }
 }
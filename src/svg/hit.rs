@@ -0,0 +1,203 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::font::Font;
+use super::layout::TextOptions;
+use super::path::{Path, Point};
+
+/// How the inside of a path is determined, as the SVG `fill-rule` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Whether points around which the outline winds `winding` times are
+    /// inside.
+    pub(crate) fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Distance within which curves are approximated for hit testing.
+const HIT_TOLERANCE: f64 = 0.01;
+
+impl Path {
+    /// Tells whether `point` is inside the filled path. Open subpaths are
+    /// closed, as when filling.
+    pub fn contains(&self, point: Point, fill_rule: FillRule) -> bool {
+        let winding = self
+            .flatten(HIT_TOLERANCE)
+            .map(|polyline| winding_number(&polyline.points, point))
+            .sum::<i32>();
+
+        fill_rule.is_inside(winding)
+    }
+
+    /// The distance between `point` and the outline of the path, infinite for
+    /// paths without segments.
+    pub fn distance_to(&self, point: Point) -> f64 {
+        let mut distance = f64::INFINITY;
+
+        for polyline in self.flatten(HIT_TOLERANCE) {
+            let points = &polyline.points;
+            let count = if polyline.is_closed {
+                points.len()
+            } else {
+                points.len() - 1
+            };
+
+            for i in 0..count {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                distance = distance.min(segment_distance(point, a, b));
+            }
+        }
+
+        distance
+    }
+}
+
+impl Font {
+    /// Finds the grapheme cluster drawn at `point`, given in the coordinates
+    /// of `text_path`, and returns its byte offset in `text`, as
+    /// `PositionedGlyph::index` and `caret_positions` do. Graphemes sharing a
+    /// ligature split its advance evenly.
+    pub fn hit_test(&self, text: &str, options: &TextOptions, point: Point) -> Option<usize> {
+        for glyph in self.layout(text, options) {
+            if glyph.path.is_empty() {
+                continue;
+            }
+
+            let path = match Path::new(glyph.path) {
+                Ok(path) => path,
+                Err(_) => continue,
+            };
+
+            if !path.contains((point.0 - glyph.x, point.1), FillRule::NonZero) {
+                continue;
+            }
+
+            let clusters = glyph
+                .text
+                .grapheme_indices(true)
+                .map(|(index, _)| glyph.index + index)
+                .collect::<Vec<usize>>();
+
+            let offset = if glyph.rtl {
+                glyph.x + glyph.advance - point.0
+            } else {
+                point.0 - glyph.x
+            };
+            let width = glyph.advance / clusters.len() as f64;
+            let cluster = if width > 0.0 {
+                (offset / width)
+                    .floor()
+                    .clamp(0.0, (clusters.len() - 1) as f64) as usize
+            } else {
+                0
+            };
+
+            return Some(clusters[cluster]);
+        }
+
+        None
+    }
+}
+
+/// How many times a closed polygon turns around `point`, counterclockwise
+/// turns with the y axis pointing up counting positively.
+pub(crate) fn winding_number(points: &[Point], point: Point) -> i32 {
+    let mut winding = 0;
+
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let side = (b.0 - a.0) * (point.1 - a.1) - (point.0 - a.0) * (b.1 - a.1);
+
+        if a.1 <= point.1 && b.1 > point.1 && side > 0.0 {
+            winding += 1;
+        } else if a.1 > point.1 && b.1 <= point.1 && side < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    winding
+}
+
+pub(crate) fn segment_distance(point: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+
+    (point.0 - a.0 - t * dx).hypot(point.1 - a.1 - t * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::{path, ARIAL_SVG_FONT};
+
+    #[test]
+    fn it_tells_whether_a_point_is_inside_a_path() {
+        let square = path("M0 0 H10 V10 H0");
+
+        assert!(square.contains((5.0, 5.0), FillRule::NonZero));
+        assert!(!square.contains((15.0, 5.0), FillRule::NonZero));
+
+        let circle = path("M0 100 A100 100 0 1 1 200 100 A100 100 0 1 1 0 100 Z");
+
+        assert!(circle.contains((100.0, 199.0), FillRule::EvenOdd));
+        assert!(!circle.contains((195.0, 195.0), FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn it_follows_the_fill_rule_for_holes() {
+        let same_direction = path("M0 0 H30 V30 H0 Z M10 10 H20 V20 H10 Z");
+        let opposite_direction = path("M0 0 H30 V30 H0 Z M10 10 V20 H20 V10 Z");
+
+        assert!(same_direction.contains((15.0, 15.0), FillRule::NonZero));
+        assert!(!same_direction.contains((15.0, 15.0), FillRule::EvenOdd));
+        assert!(!opposite_direction.contains((15.0, 15.0), FillRule::NonZero));
+        assert!(opposite_direction.contains((5.0, 15.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn it_measures_the_distance_to_the_outline() {
+        let square = path("M0 0 H10 V10 H0 Z");
+
+        assert_eq!(square.distance_to((13.0, 14.0)), 5.0);
+        assert_eq!(square.distance_to((2.0, 5.0)), 2.0);
+        assert_eq!(path("M0 0 H10 V10 H0").distance_to((0.0, 5.0)), 5.0);
+        assert_eq!(Path::default().distance_to((0.0, 0.0)), f64::INFINITY);
+    }
+
+    #[test]
+    fn it_finds_the_character_under_a_point() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let options = TextOptions::default();
+
+        assert_eq!(font.hit_test("Ho", &options, (200.0, 100.0)), Some(0));
+        assert_eq!(font.hit_test("Ho", &options, (700.0, 750.0)), Some(0));
+        assert_eq!(font.hit_test("Ho", &options, (700.0, 300.0)), None);
+        assert_eq!(font.hit_test("Ho", &options, (2048.0, 1000.0)), Some(1));
+        assert_eq!(font.hit_test("Ho", &options, (2048.0, 530.0)), None);
+
+        // Offsets count bytes, matching the caret positions.
+        let hit = font.hit_test("e\u{301}H", &options, (1389.0, 100.0));
+
+        assert_eq!(hit, Some(3));
+        assert!(font
+            .caret_positions("e\u{301}H", &options)
+            .iter()
+            .any(|(offset, _)| Some(*offset) == hit));
+        assert_eq!(font.hit_test("éH", &options, (1389.0, 100.0)), Some(2));
+    }
+}
@@ -0,0 +1,511 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::bidi::{visual_runs, Direction};
+use super::font::Font;
+use super::path::Path;
+use super::shaping::ShapedGlyph;
+
+/// Characters receiving `word_spacing`, as listed by CSS Text for word-separators.
+const WORD_SEPARATORS: [char; 7] = [
+    '\u{0020}',
+    '\u{00a0}',
+    '\u{1361}',
+    '\u{10100}',
+    '\u{10101}',
+    '\u{1039f}',
+    '\u{1091f}',
+];
+
+const TAB: char = '\t';
+
+/// A length either in font units or relative to the font's em size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Absolute(f64),
+    Em(f64),
+}
+
+impl Length {
+    pub fn resolve(&self, units_per_em: f64) -> f64 {
+        match self {
+            Length::Absolute(value) => *value,
+            Length::Em(value) => value * units_per_em,
+        }
+    }
+}
+
+/// Writing mode of the text, matched against the glyph `orientation` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOptions {
+    /// Space added after every character, like CSS `letter-spacing`.
+    pub letter_spacing: Length,
+    /// Space added to word-separator characters, like CSS `word-spacing`.
+    pub word_spacing: Length,
+    /// Distance between tab stops. Tabs use their own glyph advance when `None`.
+    pub tab_width: Option<Length>,
+    /// Whether letter-spacing is kept after the last character.
+    pub trailing_spacing: bool,
+    /// Base direction used to reorder bidirectional text.
+    pub direction: Direction,
+    /// Language of the text (e.g. "en-US"), matched against the glyph `lang` attribute.
+    pub lang: Option<String>,
+    pub orientation: Orientation,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            letter_spacing: Length::Absolute(0.0),
+            word_spacing: Length::Absolute(0.0),
+            tab_width: None,
+            trailing_spacing: false,
+            direction: Direction::Auto,
+            lang: None,
+            orientation: Orientation::Horizontal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionedGlyph {
+    /// The grapheme cluster drawn by the glyph, several for ligatures.
+    pub text: String,
+    /// Byte offset of `text` in the logical text.
+    pub index: usize,
+    /// Pen position of the glyph origin, in font units.
+    pub x: f64,
+    /// Distance to the next glyph origin, spacing included.
+    pub advance: f64,
+    /// Whether the glyph belongs to a right-to-left run.
+    pub rtl: bool,
+    /// The glyph outline, empty for blank glyphs.
+    pub path: String,
+}
+
+impl Font {
+    /// Places every grapheme cluster of `text` on the baseline, starting at `x = 0`.
+    ///
+    /// Glyphs are picked in logical order, with Arabic contextual forms and
+    /// ligatures, then laid out in visual order after bidirectional reordering
+    /// and mirroring.
+    ///
+    /// Positions in `text`, here and in `caret_positions` and `hit_test`, are
+    /// byte offsets.
+    pub fn layout(&self, text: &str, options: &TextOptions) -> Vec<PositionedGlyph> {
+        self.layout_from(text, options, 0.0)
+    }
+
+    /// Lays out `text` with the pen starting at `x`, which matters for tab stops.
+    pub(crate) fn layout_from(
+        &self,
+        text: &str,
+        options: &TextOptions,
+        x: f64,
+    ) -> Vec<PositionedGlyph> {
+        let letter_spacing = options.letter_spacing.resolve(self.units_per_em());
+        let word_spacing = options.word_spacing.resolve(self.units_per_em());
+        let tab_width = options
+            .tab_width
+            .map(|tab_width| tab_width.resolve(self.units_per_em()))
+            .filter(|tab_width| *tab_width > 0.0);
+
+        let runs = visual_runs(text, options.direction);
+
+        // Shaping happens in logical order, on characters already mirrored.
+        let mut characters = runs
+            .iter()
+            .flat_map(|run| run.characters(text))
+            .collect::<Vec<(usize, char)>>();
+        characters.sort_by_key(|(index, _)| *index);
+
+        let shaped = self.shape(
+            &characters
+                .iter()
+                .map(|(_, character)| *character)
+                .collect::<Vec<char>>(),
+            options.lang.as_deref(),
+            options.orientation,
+        );
+
+        // Glyphs are grouped by extended grapheme cluster, ligatures spanning
+        // several clusters forming a single group.
+        let boundaries = text
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+
+        let mut clusters: Vec<Vec<&ShapedGlyph>> = Vec::new();
+
+        for item in &shaped {
+            match clusters.last_mut() {
+                Some(cluster) if !boundaries.contains(&characters[item.start].0) => {
+                    cluster.push(item)
+                }
+                _ => clusters.push(vec![item]),
+            }
+        }
+
+        let mut glyphs: Vec<PositionedGlyph> = Vec::new();
+        let mut x = x;
+
+        for run in &runs {
+            let mut items = clusters
+                .iter()
+                .filter(|cluster| run.range.contains(&characters[cluster[0].start].0))
+                .collect::<Vec<&Vec<&ShapedGlyph>>>();
+
+            if run.rtl {
+                items.reverse();
+            }
+
+            for cluster in items {
+                let first = cluster[0];
+                let last = cluster[cluster.len() - 1];
+
+                let index = characters[first.start].0;
+                let end = match characters.get(last.start + last.length) {
+                    Some((end, _)) => *end,
+                    None => text.len(),
+                };
+                let character = characters[first.start].1;
+
+                let mut advance = 0.0;
+                let mut path = String::new();
+
+                for item in cluster {
+                    let item_path = item.path(self.units_per_em());
+
+                    if advance == 0.0 {
+                        path.push_str(&item_path);
+                    } else if let Ok(item_path) = Path::new(item_path) {
+                        path.push_str(&item_path.translate(advance, 0.0).to_string());
+                    }
+
+                    // Kerning applies to the glyph on the left of the pair.
+                    advance += item.horizontal_advance_x()
+                        - if run.rtl {
+                            item.kerning_before
+                        } else {
+                            item.kerning_after
+                        };
+                }
+
+                if character == TAB {
+                    if let Some(tab_width) = tab_width {
+                        advance = ((x / tab_width).floor() + 1.0) * tab_width - x;
+                    }
+                }
+
+                if WORD_SEPARATORS.contains(&character) {
+                    advance += word_spacing;
+                }
+
+                advance += letter_spacing;
+
+                glyphs.push(PositionedGlyph {
+                    text: text[index..end].to_string(),
+                    index,
+                    x,
+                    advance,
+                    rtl: run.rtl,
+                    path,
+                });
+
+                x += advance;
+            }
+        }
+
+        if !options.trailing_spacing {
+            if let Some(last) = glyphs.last_mut() {
+                last.advance -= letter_spacing;
+            }
+        }
+
+        glyphs
+    }
+
+    pub fn text_width_with(&self, text: &str, options: &TextOptions) -> f64 {
+        let glyphs = self.layout(text, options);
+
+        let width = match glyphs.last() {
+            Some(last) => last.x + last.advance,
+            None => 0.0,
+        };
+
+        width - left_margin(&glyphs)
+    }
+
+    /// Returns the caret position at every grapheme boundary of `text`, as
+    /// byte offsets in the logical text with their x coordinate.
+    ///
+    /// Clusters sharing a ligature split its advance evenly.
+    pub fn caret_positions(&self, text: &str, options: &TextOptions) -> Vec<(usize, f64)> {
+        let mut carets = Vec::new();
+
+        for glyph in self.layout(text, options) {
+            let clusters = glyph
+                .text
+                .grapheme_indices(true)
+                .map(|(index, _)| glyph.index + index)
+                .collect::<Vec<usize>>();
+            let width = glyph.advance / clusters.len() as f64;
+
+            for (i, index) in clusters.iter().enumerate() {
+                let offset = i as f64 * width;
+
+                carets.push((
+                    *index,
+                    if glyph.rtl {
+                        glyph.x + glyph.advance - offset
+                    } else {
+                        glyph.x + offset
+                    },
+                ));
+            }
+
+            if glyph.index + glyph.text.len() == text.len() {
+                carets.push((
+                    text.len(),
+                    if glyph.rtl {
+                        glyph.x
+                    } else {
+                        glyph.x + glyph.advance
+                    },
+                ));
+            }
+        }
+
+        carets.sort_by_key(|(index, _)| *index);
+
+        carets
+    }
+
+    /// Renders `text` into a single path, in font units with the y axis
+    /// pointing up as in the font.
+    pub fn text_path(&self, text: &str, options: &TextOptions) -> Path {
+        let mut path = Path::default();
+
+        for glyph in self.layout(text, options) {
+            if glyph.path.is_empty() {
+                continue;
+            }
+
+            if let Ok(glyph_path) = Path::new(glyph.path) {
+                path.append(glyph_path.translate(glyph.x, 0.0));
+            }
+        }
+
+        path
+    }
+}
+
+fn left_margin(glyphs: &[PositionedGlyph]) -> f64 {
+    for glyph in glyphs {
+        if glyph.path.is_empty() {
+            continue;
+        }
+
+        if let Ok(path) = Path::new(glyph.path.clone()) {
+            return path.bounds().unwrap().x1();
+        }
+    }
+
+    0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_adds_letter_spacing_between_characters_only() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let options = TextOptions {
+            letter_spacing: Length::Em(0.25),
+            ..TextOptions::default()
+        };
+
+        let width = font.text_width("aaa".to_string());
+        let tracking = 0.25 * font.units_per_em();
+
+        assert_eq!(
+            font.text_width_with("aaa", &options),
+            width + 2.0 * tracking
+        );
+
+        let trailing = TextOptions {
+            trailing_spacing: true,
+            ..options
+        };
+
+        assert_eq!(
+            font.text_width_with("aaa", &trailing),
+            width + 3.0 * tracking
+        );
+    }
+
+    #[test]
+    fn it_adds_word_spacing_to_spaces() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let options = TextOptions {
+            word_spacing: Length::Absolute(100.0),
+            ..TextOptions::default()
+        };
+
+        let width = font.text_width("a b c".to_string());
+
+        assert_eq!(font.text_width_with("a b c", &options), width + 200.0);
+    }
+
+    #[test]
+    fn it_aligns_tabs_on_tab_stops() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let options = TextOptions {
+            tab_width: Some(Length::Absolute(4000.0)),
+            ..TextOptions::default()
+        };
+
+        let glyphs = font.layout("a\tb\tc", &options);
+
+        assert_eq!(glyphs[2].x, 4000.0);
+        assert_eq!(glyphs[4].x, 8000.0);
+    }
+
+    #[test]
+    fn it_renders_glyphs_at_their_pen_position() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="a" d="M0 0h400v400h-400z" />
+                    <glyph unicode=" " horiz-adv-x="250" />
+                </font>
+            </svg>"#;
+        let font = Font::new(data.to_string()).unwrap();
+        let options = TextOptions {
+            letter_spacing: Length::Absolute(10.0),
+            ..TextOptions::default()
+        };
+
+        let path = font.text_path("a a", &options);
+
+        assert_eq!(path.to_string(), "M0 0h400v400h-400zM770 0h400v400h-400z");
+    }
+
+    #[test]
+    fn it_lays_out_right_to_left_text_in_visual_order() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        let glyphs = font.layout("a אב", &TextOptions::default());
+
+        let characters = glyphs.iter().map(|g| g.text.clone()).collect::<String>();
+        let indexes = glyphs.iter().map(|g| g.index).collect::<Vec<usize>>();
+
+        assert_eq!(characters, "a בא");
+        assert_eq!(indexes, vec![0, 1, 4, 2]);
+    }
+
+    #[test]
+    fn it_picks_arabic_contextual_forms() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        // lam, lam, lam: initial, medial and final forms, drawn right to left
+        let glyphs = font.layout("\u{644}\u{644}\u{644}", &TextOptions::default());
+
+        let advances = glyphs.iter().map(|g| g.advance).collect::<Vec<f64>>();
+
+        assert_eq!(glyphs[0].text, "\u{644}");
+        assert_eq!(glyphs[0].index, 4);
+        assert_eq!(advances, vec![1044.0, 442.0, 442.0]);
+    }
+
+    #[test]
+    fn it_uses_lam_alef_ligatures() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        let isolated = font.layout("\u{644}\u{627}", &TextOptions::default());
+        let after_beh = font.layout("\u{628}\u{644}\u{627}", &TextOptions::default());
+
+        assert_eq!(isolated.len(), 1);
+        assert_eq!(isolated[0].text, "\u{644}\u{627}");
+        assert_eq!(isolated[0].advance, 1116.0);
+        assert_eq!(after_beh.len(), 2);
+        assert_eq!(after_beh[0].advance, 1219.0);
+    }
+
+    #[test]
+    fn it_selects_glyphs_by_language_and_orientation() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="a" lang="tr,az" horiz-adv-x="100" />
+                    <glyph unicode="a" orientation="v" horiz-adv-x="200" />
+                    <glyph unicode="a" horiz-adv-x="300" />
+                    <glyph unicode="a" horiz-adv-x="400" />
+                </font>
+            </svg>"#;
+        let font = Font::new(data.to_string()).unwrap();
+
+        let advance = |lang: Option<&str>, orientation: Orientation| {
+            let options = TextOptions {
+                lang: lang.map(|l| l.to_string()),
+                orientation,
+                ..TextOptions::default()
+            };
+
+            font.layout("a", &options)[0].advance
+        };
+
+        assert_eq!(advance(None, Orientation::Horizontal), 300.0);
+        assert_eq!(advance(Some("az"), Orientation::Horizontal), 100.0);
+        assert_eq!(advance(Some("fr"), Orientation::Horizontal), 300.0);
+        assert_eq!(advance(None, Orientation::Vertical), 200.0);
+    }
+
+    #[test]
+    fn it_keeps_grapheme_clusters_together() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        let glyphs = font.layout(
+            "a\u{1f1eb}\u{1f1f7}e\u{301}\u{308}",
+            &TextOptions::default(),
+        );
+
+        let texts = glyphs
+            .iter()
+            .map(|g| g.text.as_str())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(texts, vec!["a", "\u{1f1eb}\u{1f1f7}", "e\u{301}\u{308}"]);
+    }
+
+    #[test]
+    fn it_computes_caret_positions_on_grapheme_boundaries() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let a = font.layout("a", &TextOptions::default())[0].advance;
+        let e = font.layout("e", &TextOptions::default())[0].advance;
+
+        let carets = font.caret_positions("ae\u{301}a", &TextOptions::default());
+
+        assert_eq!(carets, vec![(0, 0.0), (1, a), (4, a + e), (5, 2.0 * a + e)]);
+    }
+
+    #[test]
+    fn it_splits_ligature_advances_between_carets() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        // lam-alef ligature, drawn right to left
+        let carets = font.caret_positions("\u{644}\u{627}", &TextOptions::default());
+
+        assert_eq!(carets, vec![(0, 1116.0), (2, 558.0), (4, 0.0)]);
+    }
+}
@@ -0,0 +1,447 @@
+use super::collection::FontCollection;
+use super::font::Font;
+
+const GENERIC_FAMILIES: [&str; 9] = [
+    "serif",
+    "sans-serif",
+    "monospace",
+    "cursive",
+    "fantasy",
+    "system-ui",
+    "ui-serif",
+    "ui-sans-serif",
+    "ui-monospace",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FontStyle {
+    #[default]
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl FontStyle {
+    pub fn parse(value: &str) -> Option<FontStyle> {
+        match value.trim().to_lowercase().as_str() {
+            "normal" => Some(FontStyle::Normal),
+            "italic" => Some(FontStyle::Italic),
+            "oblique" => Some(FontStyle::Oblique),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        }
+    }
+}
+
+/// Parses a CSS `font-weight` value, relative keywords excepted.
+pub fn parse_font_weight(value: &str) -> Option<f64> {
+    match value.trim().to_lowercase().as_str() {
+        "normal" => Some(400.0),
+        "bold" => Some(700.0),
+        value => value
+            .parse::<f64>()
+            .ok()
+            .filter(|weight| (1.0..=1000.0).contains(weight)),
+    }
+}
+
+/// CSS `font-stretch` keywords with their percentage.
+pub(crate) const FONT_STRETCHES: [(&str, f64); 9] = [
+    ("ultra-condensed", 50.0),
+    ("extra-condensed", 62.5),
+    ("condensed", 75.0),
+    ("semi-condensed", 87.5),
+    ("normal", 100.0),
+    ("semi-expanded", 112.5),
+    ("expanded", 125.0),
+    ("extra-expanded", 150.0),
+    ("ultra-expanded", 200.0),
+];
+
+/// Parses a CSS `font-stretch` value into a percentage.
+pub fn parse_font_stretch(value: &str) -> Option<f64> {
+    let value = value.trim().to_lowercase();
+
+    match FONT_STRETCHES.iter().find(|(keyword, _)| *keyword == value) {
+        Some((_, percentage)) => Some(*percentage),
+        None => value
+            .strip_suffix('%')
+            .and_then(|percentage| percentage.parse::<f64>().ok())
+            .filter(|percentage| *percentage >= 0.0),
+    }
+}
+
+/// A font request, as given by the CSS `font` shorthand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontQuery {
+    pub families: Vec<String>,
+    pub style: FontStyle,
+    pub weight: f64,
+    /// Width as a percentage of the normal width.
+    pub stretch: f64,
+    pub small_caps: bool,
+    /// Font size in CSS pixels.
+    pub size: f64,
+    pub line_height: Option<String>,
+}
+
+impl Default for FontQuery {
+    fn default() -> Self {
+        FontQuery {
+            families: Vec::new(),
+            style: FontStyle::Normal,
+            weight: 400.0,
+            stretch: 100.0,
+            small_caps: false,
+            size: 16.0,
+            line_height: None,
+        }
+    }
+}
+
+impl FontQuery {
+    /// Parses the value of a CSS `font` shorthand, such as
+    /// `italic 600 14px "Open Sans", sans-serif`.
+    pub fn parse(value: &str) -> Result<FontQuery, String> {
+        let mut query = FontQuery::default();
+        let mut rest = value.trim();
+
+        loop {
+            let token = match rest.split_whitespace().next() {
+                Some(token) => token,
+                None => return Err("No font-size found".to_string()),
+            };
+
+            let (size, line_height) = match token.split_once('/') {
+                Some((size, line_height)) => (size, Some(line_height)),
+                None => (token, None),
+            };
+
+            rest = rest[token.len()..].trim_start();
+
+            if let Some(size) = parse_font_size(size) {
+                query.size = size;
+                query.line_height = line_height.map(|l| l.to_string());
+
+                // The line height may be separated from the slash by spaces.
+                if token.ends_with('/') || (line_height.is_none() && rest.starts_with('/')) {
+                    let after_slash = rest.trim_start_matches('/').trim_start();
+                    let line_height = after_slash.split_whitespace().next().unwrap_or("");
+
+                    query.line_height = Some(line_height.to_string());
+                    rest = after_slash[line_height.len()..].trim_start();
+                }
+
+                break;
+            }
+
+            match token.to_lowercase().as_str() {
+                "normal" => {}
+                "small-caps" => query.small_caps = true,
+                "italic" => query.style = FontStyle::Italic,
+                "oblique" => {
+                    query.style = FontStyle::Oblique;
+
+                    // An optional angle, which doesn't take part in matching.
+                    if let Some(angle) = rest.split_whitespace().next() {
+                        if angle.ends_with("deg") {
+                            rest = rest[angle.len()..].trim_start();
+                        }
+                    }
+                }
+                token => {
+                    if let Some(weight) = parse_font_weight(token) {
+                        query.weight = weight;
+                    } else if let Some(stretch) = parse_font_stretch(token) {
+                        query.stretch = stretch;
+                    } else {
+                        return Err(format!("Invalid font shorthand value: {}", token));
+                    }
+                }
+            }
+        }
+
+        query.families = parse_families(rest);
+
+        if query.families.is_empty() {
+            return Err("No font-family found".to_string());
+        }
+
+        Ok(query)
+    }
+}
+
+/// Parses a CSS `font-size` value into CSS pixels.
+fn parse_font_size(value: &str) -> Option<f64> {
+    let keyword = match value.to_lowercase().as_str() {
+        "xx-small" => Some(9.0),
+        "x-small" => Some(10.0),
+        "small" => Some(13.0),
+        "medium" => Some(16.0),
+        "large" => Some(18.0),
+        "x-large" => Some(24.0),
+        "xx-large" => Some(32.0),
+        "xxx-large" => Some(48.0),
+        _ => None,
+    };
+
+    if keyword.is_some() {
+        return keyword;
+    }
+
+    let units: [(&str, f64); 7] = [
+        ("px", 1.0),
+        ("pt", 4.0 / 3.0),
+        ("pc", 16.0),
+        ("rem", 16.0),
+        ("em", 16.0),
+        ("in", 96.0),
+        ("%", 16.0 / 100.0),
+    ];
+
+    units.iter().find_map(|(unit, factor)| {
+        value
+            .strip_suffix(unit)
+            .and_then(|number| number.parse::<f64>().ok())
+            .map(|number| number * factor)
+    })
+}
+
+/// Splits a CSS `font-family` list, commas inside quoted names being part of
+/// the name. Spaces between the words of unquoted names collapse to one.
+fn parse_families(value: &str) -> Vec<String> {
+    let mut families = Vec::new();
+    let mut family = String::new();
+    let mut quote = None;
+
+    for character in value.chars() {
+        match (quote, character) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => family.push(c),
+            (None, '"' | '\'') => quote = Some(character),
+            (None, ',') => families.push(std::mem::take(&mut family)),
+            (None, c) if c.is_whitespace() => {
+                if !family.is_empty() && !family.ends_with(' ') {
+                    family.push(' ');
+                }
+            }
+            (None, c) => family.push(c),
+        }
+    }
+
+    families.push(family);
+
+    families
+        .into_iter()
+        .map(|family| family.trim().to_string())
+        .filter(|family| !family.is_empty())
+        .collect()
+}
+
+impl FontCollection {
+    /// Resolves a CSS `font` shorthand to the best matching font.
+    pub fn resolve(&self, font: &str) -> Result<Option<&Font>, String> {
+        let query = FontQuery::parse(font)?;
+
+        Ok(self.match_font(&query).map(|index| &self.fonts()[index]))
+    }
+
+    /// Finds the font best matching `query`, following the CSS Fonts Level 4
+    /// font matching algorithm: family first, then stretch, style and weight.
+    ///
+    /// Generic families match any font, once none of the named families did.
+    pub fn match_font(&self, query: &FontQuery) -> Option<usize> {
+        let fonts = self.fonts();
+        let generic = |family: &String| GENERIC_FAMILIES.contains(&family.to_lowercase().as_str());
+
+        let named = query
+            .families
+            .iter()
+            .filter(|family| !generic(family))
+            .find_map(|family| {
+                let candidates = (0..fonts.len())
+                    .filter(|i| {
+                        fonts[*i]
+                            .family()
+                            .is_some_and(|f| f.eq_ignore_ascii_case(family))
+                    })
+                    .collect::<Vec<usize>>();
+
+                (!candidates.is_empty()).then_some(candidates)
+            });
+
+        let candidates = match named {
+            Some(candidates) => candidates,
+            None if query.families.iter().any(generic) && !fonts.is_empty() => {
+                (0..fonts.len()).collect()
+            }
+            None => return None,
+        };
+
+        let stretch = closest(
+            &candidates
+                .iter()
+                .map(|i| fonts[*i].stretch())
+                .collect::<Vec<f64>>(),
+            query.stretch,
+            query.stretch <= 100.0,
+        )?;
+        let candidates = candidates
+            .into_iter()
+            .filter(|i| fonts[*i].stretch() == stretch)
+            .collect::<Vec<usize>>();
+
+        let style_order = match query.style {
+            FontStyle::Italic => [FontStyle::Italic, FontStyle::Oblique, FontStyle::Normal],
+            FontStyle::Oblique => [FontStyle::Oblique, FontStyle::Italic, FontStyle::Normal],
+            FontStyle::Normal => [FontStyle::Normal, FontStyle::Oblique, FontStyle::Italic],
+        };
+        let style = style_order
+            .into_iter()
+            .find(|style| candidates.iter().any(|i| fonts[*i].style() == *style))?;
+        let candidates = candidates
+            .into_iter()
+            .filter(|i| fonts[*i].style() == style)
+            .collect::<Vec<usize>>();
+
+        let weights = candidates
+            .iter()
+            .map(|i| fonts[*i].weight())
+            .collect::<Vec<f64>>();
+        let weight = if (400.0..=500.0).contains(&query.weight) {
+            weights
+                .iter()
+                .copied()
+                .filter(|w| *w >= query.weight && *w <= 500.0)
+                .reduce(f64::min)
+                .or_else(|| closest(&weights, query.weight, true))
+        } else {
+            closest(&weights, query.weight, query.weight < 400.0)
+        }?;
+
+        candidates
+            .into_iter()
+            .find(|i| fonts[*i].weight() == weight)
+    }
+}
+
+/// Picks the value closest to `desired`, looking first below it when
+/// `below_first` is set and above it otherwise.
+fn closest(values: &[f64], desired: f64, below_first: bool) -> Option<f64> {
+    let below = values
+        .iter()
+        .copied()
+        .filter(|v| *v <= desired)
+        .reduce(f64::max);
+    let above = values
+        .iter()
+        .copied()
+        .filter(|v| *v >= desired)
+        .reduce(f64::min);
+
+    if below_first {
+        below.or(above)
+    } else {
+        above.or(below)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font(family: &str, weight: &str, style: &str, stretch: &str) -> Font {
+        let data = format!(
+            r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face font-family="{}" font-weight="{}" font-style="{}" font-stretch="{}" units-per-em="1000" ascent="800" descent="-200" />
+                </font>
+            </svg>"#,
+            family, weight, style, stretch
+        );
+
+        Font::new(data).unwrap()
+    }
+
+    #[test]
+    fn it_parses_the_font_shorthand() {
+        let query = FontQuery::parse(r#"italic 600 14px/1.5 "Open Sans", sans-serif"#).unwrap();
+
+        assert_eq!(query.style, FontStyle::Italic);
+        assert_eq!(query.weight, 600.0);
+        assert_eq!(query.size, 14.0);
+        assert_eq!(query.line_height, Some("1.5".to_string()));
+        assert_eq!(query.families, vec!["Open Sans", "sans-serif"]);
+    }
+
+    #[test]
+    fn it_keeps_commas_inside_quoted_families() {
+        let query =
+            FontQuery::parse(r#"14px "Foo, Bar", 'Baz ,Qux',  Open   Sans ,serif"#).unwrap();
+
+        assert_eq!(
+            query.families,
+            vec!["Foo, Bar", "Baz ,Qux", "Open Sans", "serif"]
+        );
+    }
+
+    #[test]
+    fn it_parses_keywords_and_units() {
+        let query = FontQuery::parse("small-caps bold condensed 12pt Arial").unwrap();
+
+        assert!(query.small_caps);
+        assert_eq!(query.weight, 700.0);
+        assert_eq!(query.stretch, 75.0);
+        assert_eq!(query.size, 16.0);
+        assert_eq!(query.families, vec!["Arial"]);
+
+        assert!(FontQuery::parse("italic bold").is_err());
+        assert!(FontQuery::parse("12px").is_err());
+    }
+
+    #[test]
+    fn it_matches_the_family_then_the_style_then_the_weight() {
+        let collection = FontCollection::new(vec![
+            font("Arial", "400", "normal", "normal"),
+            font("Open Sans", "400", "normal", "normal"),
+            font("Open Sans", "700", "normal", "normal"),
+            font("Open Sans", "300", "italic", "normal"),
+            font("Open Sans", "700", "italic", "normal"),
+        ]);
+
+        let matched = |css: &str| collection.match_font(&FontQuery::parse(css).unwrap());
+
+        assert_eq!(
+            matched(r#"italic 600 14px "Open Sans", sans-serif"#),
+            Some(4)
+        );
+        assert_eq!(matched(r#"italic 450 14px "Open Sans""#), Some(3));
+        assert_eq!(matched(r#"oblique 350 14px "Open Sans""#), Some(3));
+        assert_eq!(matched(r#"500 14px "Open Sans""#), Some(1));
+        assert_eq!(matched(r#"bold 14px "Open Sans""#), Some(2));
+        assert_eq!(matched("14px Helvetica, sans-serif"), Some(0));
+        assert_eq!(matched(r#"14px serif, "Open Sans""#), Some(1));
+        assert_eq!(matched("14px Helvetica"), None);
+    }
+
+    #[test]
+    fn it_matches_the_closest_stretch() {
+        let collection = FontCollection::new(vec![
+            font("Roboto", "400", "normal", "expanded"),
+            font("Roboto", "400", "normal", "condensed"),
+            font("Roboto", "400", "normal", "ultra-condensed"),
+        ]);
+
+        let resolved = collection.resolve("semi-condensed 14px Roboto").unwrap();
+
+        assert_eq!(resolved.unwrap().stretch(), 75.0);
+    }
+}
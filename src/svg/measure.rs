@@ -0,0 +1,432 @@
+use super::flatten::lerp;
+use super::path::{Element, Path, Point};
+
+/// A point of a path with the direction of the path there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointAtLength {
+    pub x: f64,
+    pub y: f64,
+    /// Direction of the path, in degrees clockwise from the x axis as for
+    /// SVG rotations.
+    pub angle: f64,
+}
+
+/// Distance within which arcs are approximated before being measured.
+const ARC_TOLERANCE: f64 = 1e-7;
+
+/// Gauss-Legendre nodes and weights on [-1, 1].
+const GAUSS_LEGENDRE: [(f64, f64); 5] = [
+    (0.0, 0.5688888888888889),
+    (-0.5384693101056831, 0.4786286704993665),
+    (0.5384693101056831, 0.4786286704993665),
+    (-0.906179845938664, 0.2369268850561891),
+    (0.906179845938664, 0.2369268850561891),
+];
+
+/// A drawing segment of the path, quadratic curves being elevated to cubic
+/// ones for measurement.
+#[derive(Debug, Clone, Copy)]
+enum Piece {
+    Line(Point, Point),
+    Cubic(Point, Point, Point, Point),
+}
+
+impl Piece {
+    fn point(&self, t: f64) -> Point {
+        match *self {
+            Piece::Line(p0, p1) => lerp(p0, p1, t),
+            Piece::Cubic(p0, c1, c2, p3) => {
+                let (a, b, c) = (lerp(p0, c1, t), lerp(c1, c2, t), lerp(c2, p3, t));
+                lerp(lerp(a, b, t), lerp(b, c, t), t)
+            }
+        }
+    }
+
+    fn derivative(&self, t: f64) -> Point {
+        match *self {
+            Piece::Line(p0, p1) => (p1.0 - p0.0, p1.1 - p0.1),
+            Piece::Cubic(p0, c1, c2, p3) => {
+                let u = 1.0 - t;
+                let (a, b, c) = (3.0 * u * u, 6.0 * u * t, 3.0 * t * t);
+
+                (
+                    a * (c1.0 - p0.0) + b * (c2.0 - c1.0) + c * (p3.0 - c2.0),
+                    a * (c1.1 - p0.1) + b * (c2.1 - c1.1) + c * (p3.1 - c2.1),
+                )
+            }
+        }
+    }
+
+    /// The direction at `t`, looking further along degenerate curves whose
+    /// control points lie on their end points.
+    fn direction(&self, t: f64) -> Point {
+        let derivative = self.derivative(t);
+
+        if derivative != (0.0, 0.0) {
+            return derivative;
+        }
+
+        match *self {
+            Piece::Line(..) => derivative,
+            Piece::Cubic(p0, c1, c2, p3) => {
+                let (from, to) = match (t < 0.5, c1 == p0, c2 == p3) {
+                    (true, true, false) => (p0, c2),
+                    (false, false, true) => (c1, p3),
+                    _ => (p0, p3),
+                };
+
+                (to.0 - from.0, to.1 - from.1)
+            }
+        }
+    }
+
+    fn split(&self, t: f64) -> (Piece, Piece) {
+        match *self {
+            Piece::Line(p0, p1) => {
+                let middle = lerp(p0, p1, t);
+                (Piece::Line(p0, middle), Piece::Line(middle, p1))
+            }
+            Piece::Cubic(p0, c1, c2, p3) => {
+                let (a, b, c) = (lerp(p0, c1, t), lerp(c1, c2, t), lerp(c2, p3, t));
+                let (d, e) = (lerp(a, b, t), lerp(b, c, t));
+                let middle = lerp(d, e, t);
+
+                (
+                    Piece::Cubic(p0, a, d, middle),
+                    Piece::Cubic(middle, e, c, p3),
+                )
+            }
+        }
+    }
+
+    fn length(&self) -> f64 {
+        self.length_with_depth(0)
+    }
+
+    fn length_with_depth(&self, depth: usize) -> f64 {
+        if let Piece::Line(p0, p1) = self {
+            return (p1.0 - p0.0).hypot(p1.1 - p0.1);
+        }
+
+        let (first, second) = self.split(0.5);
+        let whole = self.gauss_length();
+        let halves = first.gauss_length() + second.gauss_length();
+
+        if (whole - halves).abs() <= 1e-10 * halves.max(1.0) || depth >= 16 {
+            return halves;
+        }
+
+        first.length_with_depth(depth + 1) + second.length_with_depth(depth + 1)
+    }
+
+    fn gauss_length(&self) -> f64 {
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|(node, weight)| {
+                let (dx, dy) = self.derivative((node + 1.0) / 2.0);
+                weight * dx.hypot(dy) / 2.0
+            })
+            .sum()
+    }
+
+    /// The parameter at which the length from the start is `length`.
+    fn parameter_at(&self, length: f64) -> f64 {
+        let total = self.length();
+
+        if total == 0.0 {
+            return 0.0;
+        }
+
+        if let Piece::Line(..) = self {
+            return length / total;
+        }
+
+        // Newton's method, kept within a shrinking bracket.
+        let (mut low, mut high) = (0.0, 1.0);
+        let mut t = length / total;
+
+        for _ in 0..32 {
+            let error = self.split(t).0.length() - length;
+
+            if error.abs() <= 1e-9 * total.max(1.0) {
+                break;
+            }
+
+            if error > 0.0 {
+                high = t;
+            } else {
+                low = t;
+            }
+
+            let (dx, dy) = self.derivative(t);
+            let speed = dx.hypot(dy);
+            let next = t - error / speed;
+
+            t = if speed > 0.0 && next > low && next < high {
+                next
+            } else {
+                (low + high) / 2.0
+            };
+        }
+
+        t
+    }
+
+    fn element(&self, quadratic: bool) -> Element {
+        match *self {
+            Piece::Line(_, p1) => Element::LineTo(p1),
+            // Elevated quadratic curves stay quadratic once split.
+            Piece::Cubic(p0, c1, _, p3) if quadratic => {
+                Element::QuadTo((p0.0 + (c1.0 - p0.0) * 1.5, p0.1 + (c1.1 - p0.1) * 1.5), p3)
+            }
+            Piece::Cubic(_, c1, c2, p3) => Element::CubicTo(c1, c2, p3),
+        }
+    }
+}
+
+/// A measured segment, with the index of the element drawing it. Closepaths
+/// draw a line back to the start of their subpath.
+struct Segment {
+    element: usize,
+    piece: Piece,
+    length: f64,
+    quadratic: bool,
+    closing: bool,
+}
+
+impl Path {
+    /// The total length of the path, as the SVG `getTotalLength` method.
+    pub fn length(&self) -> f64 {
+        measure(&self.elements_within(ARC_TOLERANCE))
+            .iter()
+            .map(|segment| segment.length)
+            .sum()
+    }
+
+    /// The point at `length` along the path, as the SVG `getPointAtLength`
+    /// method: lengths are clamped to the path, and moves without segments
+    /// have no direction. Empty paths have no point.
+    pub fn point_at_length(&self, length: f64) -> Option<PointAtLength> {
+        let elements = self.elements_within(ARC_TOLERANCE);
+        let segments = measure(&elements);
+
+        let (segment, t) = match locate(&segments, length) {
+            Some(location) => location,
+            None => {
+                return match elements.first() {
+                    Some(Element::MoveTo((x, y))) => Some(PointAtLength {
+                        x: *x,
+                        y: *y,
+                        angle: 0.0,
+                    }),
+                    _ => None,
+                }
+            }
+        };
+
+        let (x, y) = segment.piece.point(t);
+        let (dx, dy) = segment.piece.direction(t);
+
+        Some(PointAtLength {
+            x,
+            y,
+            angle: dy.atan2(dx).to_degrees(),
+        })
+    }
+
+    /// Splits the path at `length`, clamped to the path, into the part before
+    /// and the part after. Arcs are approximated with cubic curves.
+    pub fn split_at_length(&self, length: f64) -> (Path, Path) {
+        let elements = self.elements_within(ARC_TOLERANCE);
+        let segments = measure(&elements);
+
+        let (segment, t) = match locate(&segments, length) {
+            Some(location) => location,
+            None => return (Path::from_elements(&elements), Path::default()),
+        };
+
+        let (before, after) = segment.piece.split(t);
+        let split = segment.piece.point(t);
+
+        let mut first = elements[..segment.element].to_vec();
+        first.push(before.element(segment.quadratic));
+
+        let mut second = vec![Element::MoveTo(split)];
+
+        if segment.closing {
+            second.push(Element::LineTo(match after {
+                Piece::Line(_, end) => end,
+                Piece::Cubic(.., end) => end,
+            }));
+        } else {
+            second.push(after.element(segment.quadratic));
+        }
+
+        // The rest of the subpath can't close back to the split point.
+        let start = elements[..segment.element]
+            .iter()
+            .rev()
+            .find_map(|element| match element {
+                Element::MoveTo(start) => Some(*start),
+                _ => None,
+            });
+        let mut in_subpath = true;
+
+        for element in &elements[segment.element + 1..] {
+            match element {
+                Element::MoveTo(_) => in_subpath = false,
+                Element::Close if in_subpath => {
+                    if let Some(start) = start {
+                        second.push(Element::LineTo(start));
+                    }
+
+                    in_subpath = false;
+                    continue;
+                }
+                _ => {}
+            }
+
+            second.push(*element);
+        }
+
+        (Path::from_elements(&first), Path::from_elements(&second))
+    }
+}
+
+fn measure(elements: &[Element]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+
+    for (index, element) in elements.iter().enumerate() {
+        let (piece, quadratic, closing) = match *element {
+            Element::MoveTo(point) => {
+                current = point;
+                start = point;
+                continue;
+            }
+            Element::LineTo(point) => (Piece::Line(current, point), false, false),
+            Element::QuadTo(control, point) => (
+                Piece::Cubic(
+                    current,
+                    lerp(current, control, 2.0 / 3.0),
+                    lerp(point, control, 2.0 / 3.0),
+                    point,
+                ),
+                true,
+                false,
+            ),
+            Element::CubicTo(c1, c2, point) => (Piece::Cubic(current, c1, c2, point), false, false),
+            Element::Close => (Piece::Line(current, start), false, true),
+        };
+
+        current = if closing {
+            start
+        } else {
+            match piece {
+                Piece::Line(_, end) | Piece::Cubic(.., end) => end,
+            }
+        };
+
+        segments.push(Segment {
+            element: index,
+            piece,
+            length: piece.length(),
+            quadratic,
+            closing,
+        });
+    }
+
+    segments
+}
+
+/// The segment at `length` along the path, clamped to it, and the curve
+/// parameter there.
+fn locate(segments: &[Segment], length: f64) -> Option<(&Segment, f64)> {
+    let total = segments.iter().map(|segment| segment.length).sum::<f64>();
+    let mut remaining = length.clamp(0.0, total);
+
+    // Zero length segments are skipped unless nothing else is left.
+    for segment in segments.iter().filter(|segment| segment.length > 0.0) {
+        if remaining <= segment.length {
+            return Some((segment, segment.piece.parameter_at(remaining)));
+        }
+
+        remaining -= segment.length;
+    }
+
+    match segments.iter().rev().find(|segment| segment.length > 0.0) {
+        Some(segment) => Some((segment, 1.0)),
+        None => segments.first().map(|segment| (segment, 0.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::path;
+
+    #[test]
+    fn it_measures_lines_and_closepaths() {
+        assert_eq!(path("M0 0 H10 V10 H0 Z").length(), 40.0);
+        assert_eq!(path("M0 0 L30 40 M100 100 l0 10").length(), 60.0);
+        assert_eq!(path("M10 10").length(), 0.0);
+    }
+
+    #[test]
+    fn it_measures_curves_and_arcs_accurately() {
+        let circle = path("M0 100 A100 100 0 1 1 200 100 A100 100 0 1 1 0 100 Z");
+
+        assert!((circle.length() - 200.0 * std::f64::consts::PI).abs() < 1e-6);
+        assert!((path("M0 0 C10 0 20 0 30 0").length() - 30.0).abs() < 1e-9);
+
+        let curve = path("M0 0 Q50 100 100 0 T200 0");
+        let polyline = curve.flatten(1e-4).next().unwrap();
+        let flattened = polyline
+            .points
+            .windows(2)
+            .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+            .sum::<f64>();
+
+        assert!((curve.length() - flattened).abs() < 1e-3);
+    }
+
+    #[test]
+    fn it_finds_points_at_length() {
+        let circle = path("M0 100 A100 100 0 1 1 200 100 A100 100 0 1 1 0 100 Z");
+        let quarter = circle.point_at_length(50.0 * std::f64::consts::PI).unwrap();
+
+        assert!((quarter.x - 100.0).abs() < 1e-6);
+        assert!(quarter.y.abs() < 1e-6);
+        assert!(quarter.angle.abs() < 1e-4);
+
+        let square = path("M0 0 H10 V10 H0 Z");
+
+        assert_eq!(
+            square.point_at_length(35.0),
+            Some(PointAtLength {
+                x: 0.0,
+                y: 5.0,
+                angle: -90.0,
+            })
+        );
+        assert_eq!(square.point_at_length(-5.0).unwrap().x, 0.0);
+        assert_eq!(square.point_at_length(100.0).unwrap().y, 0.0);
+        assert_eq!(path("M10 20").point_at_length(5.0).unwrap().x, 10.0);
+        assert_eq!(Path::default().point_at_length(5.0), None);
+    }
+
+    #[test]
+    fn it_splits_paths_at_length() {
+        let (before, after) = path("M0 0 H10 V10 H0 Z M20 0 h5").split_at_length(15.0);
+
+        assert_eq!(before.to_string(), "M0 0L10 0L10 5");
+        assert_eq!(after.to_string(), "M10 5L10 10L0 10L0 0M20 0L25 0");
+
+        let curve = path("M0 0 Q50 100 100 0");
+        let (before, after) = curve.split_at_length(curve.length() / 2.0);
+
+        assert_eq!(format!("{:.6}", before), "M0 0Q25 50 50 50");
+        assert_eq!(format!("{:.6}", after), "M50 50Q75 50 100 0");
+    }
+}
@@ -1,3 +1,27 @@
+pub mod arabic;
+pub mod bidi;
+pub mod boolean;
 pub mod bounds;
+pub mod builder;
+pub mod collection;
+pub mod export;
+pub mod flatten;
 pub mod font;
+pub mod hit;
+pub mod layout;
+pub mod matching;
+pub mod measure;
+pub mod normalize;
+pub mod opentype;
+pub mod orientation;
 pub mod path;
+pub mod shaping;
+pub mod simplify;
+pub mod source;
+pub mod stream;
+pub mod subpath;
+pub mod subset;
+#[cfg(test)]
+mod testing;
+pub mod truetype;
+pub mod truncate;
@@ -0,0 +1,201 @@
+use super::flatten::lerp;
+use super::path::{arc_to_cubics, Path, Point, Segment};
+use super::stream::PathSink;
+
+impl Path {
+    /// The path as absolute moves, lines, cubic curves and closepaths only.
+    /// Quadratic curves are elevated exactly and arcs approximated within
+    /// `tolerance`. Every subpath starts with a moveto.
+    pub fn cubic_segments(&self, tolerance: f64) -> Vec<Segment> {
+        let mut sink = CubicSink {
+            segments: Vec::new(),
+            tolerance: tolerance.abs().max(f64::EPSILON),
+            start: (0.0, 0.0),
+            current: (0.0, 0.0),
+            closed: true,
+        };
+        self.feed(&mut sink);
+
+        sink.segments
+    }
+
+    /// Rewrites the path with absolute `M`, `L`, `C` and `Z` commands only,
+    /// see `cubic_segments`.
+    pub fn normalize(&self, tolerance: f64) -> Path {
+        Path::from_segments(&self.cubic_segments(tolerance))
+    }
+}
+
+/// Collects the segments of a path as absolute lines and cubic curves.
+struct CubicSink {
+    segments: Vec<Segment>,
+    tolerance: f64,
+    start: Point,
+    current: Point,
+    closed: bool,
+}
+
+impl CubicSink {
+    fn push(&mut self, segment: Segment, to: Point) {
+        // Drawing after a closepath starts again from its start.
+        if self.closed {
+            self.move_to(self.start);
+        }
+
+        self.segments.push(segment);
+        self.current = to;
+    }
+}
+
+impl PathSink for CubicSink {
+    fn move_to(&mut self, to: Point) {
+        self.segments.push(Segment::MoveTo {
+            relative: false,
+            to,
+        });
+        self.start = to;
+        self.current = to;
+        self.closed = false;
+    }
+
+    fn line_to(&mut self, to: Point) {
+        self.push(
+            Segment::LineTo {
+                relative: false,
+                to,
+            },
+            to,
+        );
+    }
+
+    fn quad_to(&mut self, control: Point, to: Point) {
+        let from = if self.closed {
+            self.start
+        } else {
+            self.current
+        };
+
+        self.cubic_to(
+            lerp(from, control, 2.0 / 3.0),
+            lerp(to, control, 2.0 / 3.0),
+            to,
+        );
+    }
+
+    fn cubic_to(&mut self, control1: Point, control2: Point, to: Point) {
+        self.push(
+            Segment::CubicTo {
+                relative: false,
+                control1,
+                control2,
+                to,
+            },
+            to,
+        );
+    }
+
+    fn arc_to(
+        &mut self,
+        from: Point,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) {
+        if radii.0 == 0.0 || radii.1 == 0.0 {
+            self.line_to(to);
+            return;
+        }
+
+        for (c1, c2, to) in arc_to_cubics(
+            from,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+            self.tolerance,
+        ) {
+            self.cubic_to(c1, c2, to);
+        }
+    }
+
+    fn close(&mut self) {
+        if !self.closed {
+            self.segments.push(Segment::Close);
+            self.current = self.start;
+            self.closed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::path;
+
+    #[test]
+    fn it_normalizes_to_absolute_lines_and_cubic_curves() {
+        assert_eq!(
+            path("m10 20 h5 v5 l-5 0 z m30 0 H50 V40")
+                .normalize(0.1)
+                .to_string(),
+            "M10 20L15 20L15 25L10 25ZM40 20L50 20L50 40"
+        );
+        assert_eq!(
+            path("M0 0 C0 10 10 10 10 0 s10 -10 10 0 Z Z l5 5")
+                .normalize(0.1)
+                .to_string(),
+            "M0 0C0 10 10 10 10 0C10 -10 20 -10 20 0ZM0 0L5 5"
+        );
+        assert_eq!(
+            path("M0 0 Q15 30 30 0 T60 0").cubic_segments(0.1),
+            vec![
+                Segment::MoveTo {
+                    relative: false,
+                    to: (0.0, 0.0)
+                },
+                Segment::CubicTo {
+                    relative: false,
+                    control1: (10.0, 20.0),
+                    control2: (20.0, 20.0),
+                    to: (30.0, 0.0)
+                },
+                Segment::CubicTo {
+                    relative: false,
+                    control1: (40.0, -20.0),
+                    control2: (50.0, -20.0),
+                    to: (60.0, 0.0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_approximates_arcs_with_cubic_curves() {
+        let circle = path("M0 100 A100 100 0 1 1 200 100 A100 100 0 1 1 0 100 Z");
+        let coarse = circle.cubic_segments(10.0);
+        let fine = circle.cubic_segments(0.001);
+
+        assert_eq!(coarse.len(), 6);
+        assert!(fine.len() > coarse.len());
+        assert_eq!(
+            fine.first(),
+            Some(&Segment::MoveTo {
+                relative: false,
+                to: (0.0, 100.0)
+            })
+        );
+        assert_eq!(fine.last(), Some(&Segment::Close));
+
+        for segment in &fine[1..fine.len() - 1] {
+            match segment {
+                Segment::CubicTo { to: (x, y), .. } => {
+                    assert!(((x - 100.0).hypot(y - 100.0) - 100.0).abs() < 1e-9)
+                }
+                _ => panic!("Expected a cubic curve"),
+            }
+        }
+    }
+}
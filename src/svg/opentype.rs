@@ -0,0 +1,654 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+use ttf_parser::kern::Subtable0;
+use ttf_parser::opentype_layout::LayoutTable;
+use ttf_parser::{name_id, Face, GlyphId, OutlineBuilder, Tag};
+
+use super::font::{Font, Glyph};
+use super::matching::FontStyle;
+
+/// Widths of the `OS/2` width classes, as percentages of the normal width.
+pub(crate) const WIDTHS: [f64; 9] = [50.0, 62.5, 75.0, 87.5, 100.0, 112.5, 125.0, 150.0, 200.0];
+
+impl Font {
+    /// Loads a TrueType or OpenType font, or the first font of a collection.
+    ///
+    /// Glyphs are created for every unicode `cmap` entry, with their `glyf`
+    /// or `CFF` outline converted to an SVG path. Kerning comes from the
+    /// `GPOS` `kern` feature of the default script, or from the `kern` table
+    /// when there is none.
+    pub fn from_opentype(data: &[u8]) -> Result<Self, String> {
+        let face = match Face::parse(data, 0) {
+            Ok(face) => face,
+            Err(error) => return Err(format!("Invalid OpenType font: {}", error)),
+        };
+
+        let family = [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]
+            .iter()
+            .find_map(|id| {
+                face.names()
+                    .into_iter()
+                    .filter(|name| name.name_id == *id && name.is_unicode())
+                    .find_map(|name| name.to_string())
+            });
+
+        let style = match face.style() {
+            ttf_parser::Style::Normal => FontStyle::Normal,
+            ttf_parser::Style::Italic => FontStyle::Italic,
+            ttf_parser::Style::Oblique => FontStyle::Oblique,
+        };
+
+        let mut mapping = BTreeMap::new();
+
+        if let Some(cmap) = face.tables().cmap {
+            for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+                subtable.codepoints(|codepoint| {
+                    if let Some(glyph_id) = subtable.glyph_index(codepoint) {
+                        mapping.entry(codepoint).or_insert(glyph_id);
+                    }
+                });
+            }
+        }
+
+        let mut glyphs = Vec::new();
+        let mut ids: HashMap<GlyphId, Vec<usize>> = HashMap::new();
+
+        for (codepoint, glyph_id) in mapping {
+            let character = match char::from_u32(codepoint) {
+                Some(character) => character,
+                None => continue,
+            };
+
+            ids.entry(glyph_id).or_default().push(glyphs.len());
+
+            glyphs.push(Glyph {
+                id: glyphs.len(),
+                unicode: codepoint,
+                characters: vec![character],
+                ..glyph(&face, glyph_id)
+            });
+        }
+
+        let notdef = GlyphId(0);
+        let missing_glyph = Glyph {
+            id: glyphs.len(),
+            ..glyph(&face, notdef)
+        };
+
+        let mut kerning = HashMap::new();
+
+        for (left, right, value) in kerning_pairs(&face, &ids) {
+            for first in &ids[&left] {
+                for second in &ids[&right] {
+                    kerning.entry((*first, *second)).or_insert(-value);
+                }
+            }
+        }
+
+        Ok(Font {
+            id: None,
+            horizontal_advance_x: missing_glyph.horizontal_advance_x,
+            units_per_em: face.units_per_em() as f64,
+            ascent: face.ascender() as f64,
+            descent: face.descender() as f64,
+            family,
+            weight: face.weight().to_number() as f64,
+            style,
+            stretch: WIDTHS[face.width().to_number() as usize - 1],
+            font_face: Vec::new(),
+            glyphs,
+            missing_glyph: Some(missing_glyph),
+            kerning,
+        })
+    }
+}
+
+/// A glyph of the face, without any character.
+fn glyph(face: &Face, glyph_id: GlyphId) -> Glyph {
+    let mut path = SvgPath(String::new());
+    face.outline_glyph(glyph_id, &mut path);
+
+    Glyph {
+        id: 0,
+        name: face.glyph_name(glyph_id).map(|name| name.to_string()),
+        unicode: 0,
+        characters: Vec::new(),
+        arabic_form: None,
+        lang: Vec::new(),
+        orientation: None,
+        horizontal_advance_x: face.glyph_hor_advance(glyph_id).unwrap_or(0) as f64,
+        path: path.0,
+    }
+}
+
+/// Horizontal kerning between mapped glyphs, as advance adjustments in font
+/// units.
+///
+/// Class based subtables are expanded class by class, each pair of classes
+/// being looked up once, rather than glyph by glyph.
+fn kerning_pairs(face: &Face, ids: &HashMap<GlyphId, Vec<usize>>) -> Vec<(GlyphId, GlyphId, f64)> {
+    let mut glyph_ids = ids.keys().copied().collect::<Vec<GlyphId>>();
+    glyph_ids.sort();
+
+    let mut pairs = Vec::new();
+
+    if let Some(gpos) = face.tables().gpos {
+        let lookups = kern_lookups(&gpos);
+
+        for lookup in lookups.iter().filter_map(|index| gpos.lookups.get(*index)) {
+            for subtable in lookup.subtables.into_iter::<PositioningSubtable>() {
+                match subtable {
+                    PositioningSubtable::Pair(PairAdjustment::Format1 { coverage, sets }) => {
+                        for left in &glyph_ids {
+                            let set = match coverage.get(*left).and_then(|index| sets.get(index)) {
+                                Some(set) => set,
+                                None => continue,
+                            };
+
+                            for right in &glyph_ids {
+                                let value = set.get(*right).map(|(first, _)| first.x_advance);
+
+                                if let Some(value) = value.filter(|value| *value != 0) {
+                                    pairs.push((*left, *right, value as f64));
+                                }
+                            }
+                        }
+                    }
+                    PositioningSubtable::Pair(PairAdjustment::Format2 {
+                        coverage,
+                        classes,
+                        matrix,
+                    }) => class_pairs(
+                        &glyph_ids,
+                        |left| coverage.contains(left).then(|| classes.0.get(left)),
+                        |right| Some(classes.1.get(right)),
+                        |first, second| {
+                            matrix
+                                .get((first, second))
+                                .map(|(first, _)| first.x_advance)
+                        },
+                        &mut pairs,
+                    ),
+                    _ => {}
+                }
+            }
+        }
+
+        if !lookups.is_empty() {
+            return pairs;
+        }
+    }
+
+    let kern = face.raw_face().table(Tag::from_bytes(b"kern"));
+
+    for subtable in kern.map(kern_subtables).unwrap_or_default() {
+        match subtable.format {
+            0 => {
+                if let Some(table) = Subtable0::parse(subtable.data) {
+                    for pair in table.pairs {
+                        if ids.contains_key(&pair.left()) && ids.contains_key(&pair.right()) {
+                            pairs.push((pair.left(), pair.right(), pair.value as f64));
+                        }
+                    }
+                }
+            }
+            2 => {
+                // Class values are offsets from the start of the subtable,
+                // left ones pointing at rows of the kerning array.
+                let data = subtable.data;
+                let offset = |at: usize| {
+                    read_u16(data, at)
+                        .and_then(|offset| (offset as usize).checked_sub(subtable.header_length))
+                };
+                let (left_table, right_table, array) = (offset(2), offset(4), offset(6));
+                let class = |table: Option<usize>, glyph: GlyphId| {
+                    let table = table?;
+                    let index = glyph.0.checked_sub(read_u16(data, table)?)?;
+
+                    if index >= read_u16(data, table + 2)? {
+                        return None;
+                    }
+
+                    read_u16(data, table + 4 + index as usize * 2)
+                };
+
+                class_pairs(
+                    &glyph_ids,
+                    |left| class(left_table, left).filter(|row| Some(*row as usize) >= array),
+                    |right| Some(class(right_table, right).unwrap_or(0)),
+                    |row, column| {
+                        let at =
+                            (row as usize + column as usize).checked_sub(subtable.header_length)?;
+
+                        read_u16(data, at).map(|value| value as i16)
+                    },
+                    &mut pairs,
+                );
+            }
+            3 => {
+                let data = subtable.data;
+                let glyph_count = read_u16(data, 0).unwrap_or(0);
+                let (values, lefts, rights) = match data.get(2..5) {
+                    Some(counts) => (counts[0] as usize, counts[1], counts[2]),
+                    None => continue,
+                };
+                let left_classes = 6 + values * 2;
+                let right_classes = left_classes + glyph_count as usize;
+                let indices = right_classes + glyph_count as usize;
+                let class = |classes: usize, glyph: GlyphId| {
+                    if glyph.0 >= glyph_count {
+                        return None;
+                    }
+
+                    data.get(classes + glyph.0 as usize)
+                        .map(|class| *class as u16)
+                };
+
+                class_pairs(
+                    &glyph_ids,
+                    |left| class(left_classes, left).filter(|class| *class <= lefts as u16),
+                    |right| class(right_classes, right).filter(|class| *class <= rights as u16),
+                    |left, right| {
+                        let index = left as usize * rights as usize + right as usize;
+
+                        if index >= lefts as usize * rights as usize {
+                            return None;
+                        }
+
+                        let value = *data.get(indices + index)? as usize;
+
+                        if value >= values {
+                            return None;
+                        }
+
+                        read_u16(data, 6 + value * 2).map(|value| value as i16)
+                    },
+                    &mut pairs,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    pairs
+}
+
+/// The lookups of the `kern` feature of the default script, or of the latin
+/// script when there is no default one.
+fn kern_lookups(gpos: &LayoutTable) -> Vec<u16> {
+    let script = gpos
+        .scripts
+        .find(Tag::from_bytes(b"DFLT"))
+        .or_else(|| gpos.scripts.find(Tag::from_bytes(b"latn")));
+
+    let language = match script.and_then(|script| script.default_language) {
+        Some(language) => language,
+        None => return Vec::new(),
+    };
+
+    language
+        .feature_indices
+        .into_iter()
+        .filter_map(|index| gpos.features.get(index))
+        .filter(|feature| feature.tag == Tag::from_bytes(b"kern"))
+        .flat_map(|feature| feature.lookup_indices)
+        .collect()
+}
+
+/// Looks the kerning up once for each pair of left and right classes the
+/// glyphs fall in, adding a pair for each glyph of both classes when it isn't
+/// zero. Glyphs without a class aren't kerned.
+fn class_pairs(
+    glyph_ids: &[GlyphId],
+    left_class: impl Fn(GlyphId) -> Option<u16>,
+    right_class: impl Fn(GlyphId) -> Option<u16>,
+    value: impl Fn(u16, u16) -> Option<i16>,
+    pairs: &mut Vec<(GlyphId, GlyphId, f64)>,
+) {
+    let classes = |class: &dyn Fn(GlyphId) -> Option<u16>| {
+        let mut classes = BTreeMap::<u16, Vec<GlyphId>>::new();
+
+        for glyph_id in glyph_ids {
+            if let Some(class) = class(*glyph_id) {
+                classes.entry(class).or_default().push(*glyph_id);
+            }
+        }
+
+        classes
+    };
+
+    let rights = classes(&right_class);
+
+    for (left, left_glyphs) in classes(&left_class) {
+        for (right, right_glyphs) in &rights {
+            let value = match value(left, *right).filter(|value| *value != 0) {
+                Some(value) => value as f64,
+                None => continue,
+            };
+
+            for first in &left_glyphs {
+                for second in right_glyphs {
+                    pairs.push((*first, *second, value));
+                }
+            }
+        }
+    }
+}
+
+/// A horizontal subtable of a `kern` table, without its header.
+struct KernSubtable<'a> {
+    format: u8,
+    header_length: usize,
+    data: &'a [u8],
+}
+
+/// The horizontal subtables of an OpenType or Apple `kern` table, leaving
+/// out cross-stream and variation ones.
+fn kern_subtables(data: &[u8]) -> Vec<KernSubtable<'_>> {
+    let mut subtables = Vec::new();
+    let apple = read_u16(data, 0) == Some(1);
+
+    let (count, mut at) = if apple {
+        (read_u16(data, 6).unwrap_or(0) as usize, 8)
+    } else {
+        (read_u16(data, 2).unwrap_or(0) as usize, 4)
+    };
+
+    for _ in 0..count {
+        let (length, format, horizontal, header_length) = if apple {
+            let length = (read_u16(data, at).unwrap_or(0) as usize) << 16
+                | read_u16(data, at + 2).unwrap_or(0) as usize;
+            let coverage = data.get(at + 4).copied().unwrap_or(0);
+
+            (
+                length,
+                data.get(at + 5).copied().unwrap_or(0),
+                coverage & 0xe0 == 0,
+                8,
+            )
+        } else {
+            // A single subtable may be longer than its 16 bits length says.
+            let length = match count {
+                1 => data.len() - at,
+                _ => read_u16(data, at + 2).unwrap_or(0) as usize,
+            };
+            let coverage = data.get(at + 5).copied().unwrap_or(0);
+
+            (
+                length,
+                data.get(at + 4).copied().unwrap_or(0),
+                coverage & 0x05 == 0x01,
+                6,
+            )
+        };
+
+        let subtable = match data.get(at + header_length..at + length.max(header_length)) {
+            Some(subtable) => subtable,
+            None => break,
+        };
+
+        if horizontal {
+            subtables.push(KernSubtable {
+                format,
+                header_length,
+                data: subtable,
+            });
+        }
+
+        at += length.max(header_length);
+    }
+
+    subtables
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// Writes outlines as SVG path data.
+struct SvgPath(String);
+
+impl OutlineBuilder for SvgPath {
+    fn move_to(&mut self, x: f32, y: f32) {
+        write!(self.0, "M{} {}", x, y).unwrap();
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        write!(self.0, "L{} {}", x, y).unwrap();
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        write!(self.0, "Q{} {} {} {}", x1, y1, x, y).unwrap();
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        write!(self.0, "C{} {} {} {} {} {}", x1, y1, x2, y2, x, y).unwrap();
+    }
+
+    fn close(&mut self) {
+        self.0.push('Z');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Big endian 16 bits values, 32 bits values being written as two halves.
+    fn words(values: &[i32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| (*value as u16).to_be_bytes())
+            .collect()
+    }
+
+    fn sfnt(mut tables: Vec<(&[u8; 4], Vec<u8>)>) -> Vec<u8> {
+        tables.sort_by_key(|(tag, _)| **tag);
+
+        let mut data = words(&[1, 0, tables.len() as i32, 0, 0, 0]);
+        let mut offset = 12 + 16 * tables.len();
+
+        for (tag, table) in &tables {
+            data.extend_from_slice(*tag);
+            data.extend(words(&[0, 0, 0, offset as i32, 0, table.len() as i32]));
+            offset += table.len().div_ceil(4) * 4;
+        }
+
+        for (_, table) in &tables {
+            data.extend(table);
+            data.resize(data.len().div_ceil(4) * 4, 0);
+        }
+
+        data
+    }
+
+    /// A font with `.notdef`, `A` (a square) and `V` (a triangle), the pair
+    /// `AV` being kerned by -80 in the `kern` table.
+    fn tables() -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let square = words(&[
+            1, 100, 0, 500, 700, 3, 0, 0x0101, 0x0101, 100, 400, 0, -400, 0, 0, 700, 0,
+        ]);
+        let triangle = words(&[
+            1, 0, 0, 600, 700, 2, 0, 0x0101, 0x0100, 0, 300, 300, 700, -700, 700,
+        ]);
+
+        let mut glyf = square.clone();
+        glyf.extend(&triangle);
+
+        let loca = words(&[
+            0,
+            0,
+            square.len() as i32 / 2,
+            (square.len() + triangle.len()) as i32 / 2,
+        ]);
+
+        let mut head = words(&[1, 0, 0, 0, 0, 0, 0x5f0f, 0x3cf5, 0, 1000]);
+        head.extend(vec![0; 16]);
+        head.extend(words(&[0, -200, 600, 700, 0, 8, 2, 0, 0]));
+
+        let hhea = words(&[
+            1, 0, 800, -200, 0, 600, 0, 0, 600, 1, 0, 0, 0, 0, 0, 0, 0, 3,
+        ]);
+        let maxp = words(&[0, 0x5000, 3]);
+        let hmtx = words(&[500, 0, 600, 100, 600, 0]);
+
+        let mut cmap = words(&[0, 1, 3, 10, 0, 12, 12, 0, 0, 40, 0, 0, 0, 2]);
+        cmap.extend(words(&[0, 0x41, 0, 0x41, 0, 1, 0, 0x56, 0, 0x56, 0, 2]));
+
+        let kern = words(&[0, 1, 0, 20, 1, 1, 6, 0, 0, 1, 2, -80]);
+
+        let mut os2 = words(&[0, 0, 700, 3, 0]);
+        os2.resize(62, 0);
+        os2.extend(words(&[1]));
+        os2.resize(78, 0);
+
+        let family = "Test Sans"
+            .encode_utf16()
+            .map(|c| c as i32)
+            .collect::<Vec<i32>>();
+        let mut name = words(&[0, 1, 18, 3, 1, 0x409, 1, family.len() as i32 * 2, 0]);
+        name.extend(words(&family));
+
+        let post = words(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 36, 57]);
+
+        vec![
+            (b"glyf", glyf),
+            (b"loca", loca),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"maxp", maxp),
+            (b"hmtx", hmtx),
+            (b"cmap", cmap),
+            (b"kern", kern),
+            (b"OS/2", os2),
+            (b"name", name),
+            (b"post", post),
+        ]
+    }
+
+    #[test]
+    fn it_loads_metrics_from_opentype_tables() {
+        let font = Font::from_opentype(&sfnt(tables())).unwrap();
+
+        assert_eq!(font.units_per_em(), 1000.0);
+        assert_eq!(font.ascent(), 800.0);
+        assert_eq!(font.descent(), -200.0);
+        assert_eq!(font.family(), Some("Test Sans"));
+        assert_eq!(font.weight(), 700.0);
+        assert_eq!(font.stretch(), 75.0);
+        assert_eq!(font.style(), FontStyle::Italic);
+        assert_eq!(font.missing_glyph_advance(), Some(500.0));
+    }
+
+    #[test]
+    fn it_loads_glyphs_from_opentype_tables() {
+        let font = Font::from_opentype(&sfnt(tables())).unwrap();
+
+        assert!(font.has_glyph('A'));
+        assert!(font.has_glyph('V'));
+        assert!(!font.has_glyph('B'));
+
+        let a = font.glyph_for('A').unwrap();
+
+        assert_eq!(a.name.as_deref(), Some("A"));
+        assert_eq!(a.horizontal_advance_x, 600.0);
+        assert_eq!(a.path, "M100 0L500 0L500 700L100 700L100 0Z");
+        assert_eq!(font.text_width("AA".to_string()), 1100.0);
+    }
+
+    #[test]
+    fn it_loads_kerning_from_the_kern_table() {
+        let font = Font::from_opentype(&sfnt(tables())).unwrap();
+
+        assert_eq!(font.kerning('A', 'V'), -80.0);
+        assert_eq!(font.kerning('V', 'A'), 0.0);
+        assert_eq!(font.text_width("AV".to_string()), 1020.0);
+    }
+
+    /// A `GPOS` table whose `kern` feature, for the default language of
+    /// `script`, has a single pair adjustment lookup with `subtable`.
+    fn gpos(script: &[u8; 4], subtable: &[i32]) -> Vec<u8> {
+        let mut gpos = words(&[1, 0, 10, 30, 44, 1]);
+        gpos.extend(script);
+        gpos.extend(words(&[8, 4, 0, 0, 0xffff, 1, 0, 1]));
+        gpos.extend(b"kern");
+        gpos.extend(words(&[8, 0, 1, 0, 1, 4, 2, 0, 1, 8]));
+        gpos.extend(words(subtable));
+        gpos
+    }
+
+    /// Pair adjustment for AV, glyph by glyph.
+    const PAIR_ADJUSTMENT: [i32; 12] = [1, 12, 4, 0, 1, 18, 1, 1, 1, 1, 2, -50];
+
+    #[test]
+    fn it_prefers_gpos_kerning() {
+        let mut tables = tables();
+        tables.push((b"GPOS", gpos(b"DFLT", &PAIR_ADJUSTMENT)));
+
+        let font = Font::from_opentype(&sfnt(tables)).unwrap();
+
+        assert_eq!(font.kerning('A', 'V'), -50.0);
+    }
+
+    #[test]
+    fn it_loads_class_kerning_from_gpos() {
+        // A is in the first class 1 and V in the second class 1.
+        let mut tables = tables();
+        tables.push((
+            b"GPOS",
+            gpos(
+                b"latn",
+                &[
+                    2, 24, 4, 0, 30, 40, 2, 2, 0, 0, 0, -30, 1, 1, 1, 2, 1, 1, 1, 1, 2, 1, 2, 2, 1,
+                ],
+            ),
+        ));
+
+        let font = Font::from_opentype(&sfnt(tables)).unwrap();
+
+        assert_eq!(font.kerning('A', 'V'), -30.0);
+        assert_eq!(font.kerning('A', 'A'), 0.0);
+        assert_eq!(font.kerning('V', 'A'), 0.0);
+    }
+
+    #[test]
+    fn it_only_uses_the_kern_feature_of_the_default_script() {
+        let mut tables = tables();
+        tables.push((b"GPOS", gpos(b"cyrl", &PAIR_ADJUSTMENT)));
+
+        let font = Font::from_opentype(&sfnt(tables)).unwrap();
+
+        assert_eq!(font.kerning('A', 'V'), -80.0);
+    }
+
+    #[test]
+    fn it_loads_class_kerning_from_the_kern_table() {
+        // A points at the second row of the kerning array, V at the first
+        // one, and V is in the second column.
+        let kern = words(&[
+            0, 1, 0, 38, 0x0201, 4, 14, 22, 30, 1, 2, 34, 30, 1, 2, 0, 2, 0, 0, 0, -40,
+        ]);
+        let mut tables = tables()
+            .into_iter()
+            .filter(|(tag, _)| *tag != b"kern")
+            .collect::<Vec<(&[u8; 4], Vec<u8>)>>();
+        tables.push((b"kern", kern));
+
+        let font = Font::from_opentype(&sfnt(tables)).unwrap();
+
+        assert_eq!(font.kerning('A', 'V'), -40.0);
+        assert_eq!(font.kerning('V', 'V'), 0.0);
+        assert_eq!(font.kerning('A', 'A'), 0.0);
+    }
+
+    #[test]
+    fn it_loads_opentype_fonts_from_bytes() {
+        let font = Font::from_bytes(&sfnt(tables())).unwrap();
+
+        assert!(font.has_glyph('A'));
+        assert!(Font::from_opentype(b"OTTO").is_err());
+    }
+}
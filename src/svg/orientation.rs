@@ -0,0 +1,229 @@
+use super::hit::{segment_distance, winding_number};
+use super::path::{Path, Point, Segment};
+
+/// The direction in which a contour runs, as seen with the y axis pointing up
+/// as in glyph outlines. It's the other way around on an SVG canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Winding {
+    /// The winding of a contour enclosing `area`, `None` when it's zero.
+    fn of(area: f64) -> Option<Winding> {
+        if area > 0.0 {
+            Some(Winding::CounterClockwise)
+        } else if area < 0.0 {
+            Some(Winding::Clockwise)
+        } else {
+            None
+        }
+    }
+
+    pub fn reversed(self) -> Winding {
+        match self {
+            Winding::Clockwise => Winding::CounterClockwise,
+            Winding::CounterClockwise => Winding::Clockwise,
+        }
+    }
+}
+
+/// Distance within which arcs are approximated to measure areas.
+const ARC_TOLERANCE: f64 = 1e-6;
+
+/// Distance within which contours are flattened to find how they nest.
+const NESTING_TOLERANCE: f64 = 0.01;
+
+impl Path {
+    /// The area enclosed by the path, counterclockwise contours counting
+    /// positively and clockwise ones negatively. Open subpaths are closed.
+    pub fn signed_area(&self) -> f64 {
+        self.subpaths().map(|subpath| subpath_area(&subpath)).sum()
+    }
+
+    /// The winding of every subpath, `None` for those enclosing no area.
+    pub fn windings(&self) -> Vec<Option<Winding>> {
+        self.subpaths()
+            .map(|subpath| Winding::of(subpath_area(&subpath)))
+            .collect()
+    }
+
+    /// Reverses contours so that those inside an even number of others run
+    /// in the `outer` direction and holes the other way, which nonzero and
+    /// evenodd fills then paint alike. The path is returned unchanged when
+    /// no contour needs reversing.
+    pub fn normalize_orientation(&self, outer: Winding) -> Path {
+        let subpaths = self.subpaths().collect::<Vec<Path>>();
+        let areas = subpaths.iter().map(subpath_area).collect::<Vec<f64>>();
+        let polylines = subpaths
+            .iter()
+            .map(|subpath| {
+                subpath
+                    .flatten(NESTING_TOLERANCE)
+                    .next()
+                    .map(|polyline| polyline.points)
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<Vec<Point>>>();
+
+        let mut path = Path::default();
+        let mut reversed = false;
+
+        for (i, subpath) in subpaths.into_iter().enumerate() {
+            // A contour can only be inside larger ones.
+            let depth = (0..areas.len())
+                .filter(|&j| areas[j].abs() > areas[i].abs())
+                .filter(|&j| encloses(&polylines[j], &polylines[i]))
+                .count();
+            let wanted = if depth % 2 == 0 {
+                outer
+            } else {
+                outer.reversed()
+            };
+
+            match Winding::of(areas[i]) {
+                Some(winding) if winding != wanted => {
+                    path.append(subpath.reverse());
+                    reversed = true;
+                }
+                _ => path.append(subpath),
+            }
+        }
+
+        if reversed {
+            path
+        } else {
+            self.clone()
+        }
+    }
+}
+
+/// The signed area of a subpath, from Green's theorem.
+fn subpath_area(subpath: &Path) -> f64 {
+    let mut area = 0.0;
+    let mut start = (0.0, 0.0);
+    let mut current = (0.0, 0.0);
+
+    for segment in subpath.cubic_segments(ARC_TOLERANCE) {
+        match segment {
+            Segment::MoveTo { to, .. } => {
+                start = to;
+                current = to;
+            }
+            Segment::LineTo { to, .. } => {
+                area += line_area(current, to);
+                current = to;
+            }
+            Segment::CubicTo {
+                control1,
+                control2,
+                to,
+                ..
+            } => {
+                area += cubic_area(current, control1, control2, to);
+                current = to;
+            }
+            _ => {}
+        }
+    }
+
+    area + line_area(current, start)
+}
+
+fn line_area(p0: Point, p1: Point) -> f64 {
+    (p0.0 * p1.1 - p1.0 * p0.1) / 2.0
+}
+
+fn cubic_area(p0: Point, p1: Point, p2: Point, p3: Point) -> f64 {
+    (p0.0 * (6.0 * p1.1 + 3.0 * p2.1 + p3.1)
+        + 3.0 * (p1.0 * (-2.0 * p0.1 + p2.1 + p3.1) - p2.0 * (p0.1 + p1.1 - 2.0 * p3.1))
+        - p3.0 * (p0.1 + 3.0 * p1.1 + 6.0 * p2.1))
+        / 20.0
+}
+
+/// Whether the `inner` polygon lies inside the `outer` one, judging from its
+/// first point which isn't on `outer`.
+fn encloses(outer: &[Point], inner: &[Point]) -> bool {
+    let on_outline = |point: Point| {
+        (0..outer.len()).any(|i| {
+            segment_distance(point, outer[i], outer[(i + 1) % outer.len()]) <= f64::EPSILON
+        })
+    };
+
+    match inner.iter().find(|point| !on_outline(**point)) {
+        Some(point) if !outer.is_empty() => winding_number(outer, *point) != 0,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::{path, ARIAL_SVG_FONT};
+
+    #[test]
+    fn it_measures_the_signed_area() {
+        assert_eq!(path("M0 0 H10 V10 H0 Z").signed_area(), 100.0);
+        assert_eq!(path("M0 0 V10 H10 V0").signed_area(), -100.0);
+        assert!((path("M0 0 Q5 10 10 0 Z").signed_area() + 100.0 / 3.0).abs() < 1e-9);
+
+        let circle = path("M0 100 A100 100 0 1 0 200 100 A100 100 0 1 0 0 100 Z");
+
+        assert!((circle.signed_area() + std::f64::consts::PI * 10000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn it_tells_the_winding_of_every_subpath() {
+        assert_eq!(
+            path("M0 0 H10 V10 Z M0 0 V10 H10 Z M5 5 L10 10 M0 0").windings(),
+            vec![
+                Some(Winding::CounterClockwise),
+                Some(Winding::Clockwise),
+                None,
+                None
+            ]
+        );
+
+        let font = crate::svg::font::Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let o = font
+            .glyphs()
+            .iter()
+            .find(|g| g.unicode == 'o' as u32)
+            .unwrap();
+        let windings = path(&o.path).windings();
+
+        assert_eq!(windings.len(), 2);
+        assert_ne!(windings[0], windings[1]);
+    }
+
+    #[test]
+    fn it_orients_contours_by_nesting() {
+        let nested =
+            path("M0 0 H30 V30 H0 Z M5 5 H25 V25 H5 Z M10 10 V20 H20 V10 Z M40 0 V10 H50 Z");
+
+        assert_eq!(
+            nested.normalize_orientation(Winding::Clockwise).to_string(),
+            "M0 0L0 30L30 30L30 0L0 0ZM5 5L25 5L25 25L5 25Z\
+             M10 10L10 20L20 20L20 10ZM40 0L40 10L50 10Z"
+        );
+        assert_eq!(
+            nested
+                .normalize_orientation(Winding::CounterClockwise)
+                .windings(),
+            vec![
+                Some(Winding::CounterClockwise),
+                Some(Winding::Clockwise),
+                Some(Winding::CounterClockwise),
+                Some(Winding::CounterClockwise)
+            ]
+        );
+
+        let square = path("M0 0 V10 H10 V0 Z");
+
+        assert_eq!(
+            square.normalize_orientation(Winding::Clockwise).to_string(),
+            "M0 0V10H10V0Z"
+        );
+    }
+}
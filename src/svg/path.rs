@@ -1,9 +1,9 @@
 use super::bounds::Bounds;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::fmt::Error;
+use std::fmt::{self, Display, Error};
 
-const POSSIBLE_PATH_CHARS: &[char] = &[
+pub(crate) const POSSIBLE_PATH_CHARS: &[char] = &[
     'M', 'm', 'L', 'l', 'H', 'h', 'V', 'v', 'C', 'c', 'S', 's', 'Q', 'q', 'T', 't', 'A', 'a', 'Z',
     'z',
 ];
@@ -41,11 +41,60 @@ fn get_commands_from_path_string(path: &str) -> Result<Vec<Command>, Error> {
     Ok(commands)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct Path {
     commands: Vec<Command>,
 }
 
+/// A point in user units.
+pub type Point = (f64, f64);
+
+/// A drawing operation in absolute coordinates, horizontal and vertical
+/// lines, smooth curves and arcs being expanded to the other operations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Element {
+    MoveTo(Point),
+    LineTo(Point),
+    QuadTo(Point, Point),
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+/// A segment of a path as written, its coordinates being relative to the
+/// current point when `relative`. Horizontal and vertical lines become lines
+/// and smooth curves get their reflected control point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    MoveTo {
+        relative: bool,
+        to: Point,
+    },
+    LineTo {
+        relative: bool,
+        to: Point,
+    },
+    QuadTo {
+        relative: bool,
+        control: Point,
+        to: Point,
+    },
+    CubicTo {
+        relative: bool,
+        control1: Point,
+        control2: Point,
+        to: Point,
+    },
+    ArcTo {
+        relative: bool,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    },
+    Close,
+}
+
 impl Clone for Command {
     fn clone(&self) -> Self {
         Command {
@@ -62,6 +111,343 @@ impl Path {
         Ok(Path { commands })
     }
 
+    /// A path drawing the given elements with absolute commands.
+    pub(crate) fn from_elements(elements: &[Element]) -> Path {
+        let commands = elements
+            .iter()
+            .map(|element| match *element {
+                Element::MoveTo(p) => Command {
+                    command: 'M',
+                    args: vec![p.0, p.1],
+                },
+                Element::LineTo(p) => Command {
+                    command: 'L',
+                    args: vec![p.0, p.1],
+                },
+                Element::QuadTo(c, p) => Command {
+                    command: 'Q',
+                    args: vec![c.0, c.1, p.0, p.1],
+                },
+                Element::CubicTo(c1, c2, p) => Command {
+                    command: 'C',
+                    args: vec![c1.0, c1.1, c2.0, c2.1, p.0, p.1],
+                },
+                Element::Close => Command {
+                    command: 'Z',
+                    args: Vec::new(),
+                },
+            })
+            .collect();
+
+        Path { commands }
+    }
+
+    /// A path drawing the given segments.
+    pub fn from_segments(segments: &[Segment]) -> Path {
+        let command = |relative: bool, command: char, args: Vec<f64>| Command {
+            command: if relative {
+                command.to_ascii_lowercase()
+            } else {
+                command
+            },
+            args,
+        };
+
+        let commands = segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::MoveTo { relative, to } => command(relative, 'M', vec![to.0, to.1]),
+                Segment::LineTo { relative, to } => command(relative, 'L', vec![to.0, to.1]),
+                Segment::QuadTo {
+                    relative,
+                    control,
+                    to,
+                } => command(relative, 'Q', vec![control.0, control.1, to.0, to.1]),
+                Segment::CubicTo {
+                    relative,
+                    control1,
+                    control2,
+                    to,
+                } => command(
+                    relative,
+                    'C',
+                    vec![control1.0, control1.1, control2.0, control2.1, to.0, to.1],
+                ),
+                Segment::ArcTo {
+                    relative,
+                    radii,
+                    x_axis_rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => command(
+                    relative,
+                    'A',
+                    vec![
+                        radii.0,
+                        radii.1,
+                        x_axis_rotation,
+                        large_arc as u8 as f64,
+                        sweep as u8 as f64,
+                        to.0,
+                        to.1,
+                    ],
+                ),
+                Segment::Close => command(false, 'Z', Vec::new()),
+            })
+            .collect();
+
+        Path { commands }
+    }
+
+    /// The segments of the path, one per set of command arguments.
+    pub fn segments(&self) -> impl Iterator<Item = Segment> {
+        let mut segments = Vec::new();
+        let mut reader = SegmentReader::default();
+
+        for command in &self.commands {
+            if command.command.eq_ignore_ascii_case(&'z') {
+                segments.push(reader.read(command.command, false, &[]));
+                continue;
+            }
+
+            for (i, args) in command
+                .args
+                .chunks_exact(arity(command.command))
+                .enumerate()
+            {
+                segments.push(reader.read(command.command, i == 0, args));
+            }
+        }
+
+        segments.into_iter()
+    }
+
+    /// Returns a copy of the path moved by `dx` and `dy`.
+    pub fn translate(&self, dx: f64, dy: f64) -> Path {
+        let mut commands = Vec::new();
+
+        for (index, command) in self.commands.iter().enumerate() {
+            // A leading relative moveto is absolute, and so is its first pair only.
+            if index == 0 && command.command == 'm' && command.args.len() >= 2 {
+                commands.push(Command {
+                    command: 'M',
+                    args: vec![command.args[0] + dx, command.args[1] + dy],
+                });
+
+                if command.args.len() > 2 {
+                    commands.push(Command {
+                        command: 'l',
+                        args: command.args[2..].to_vec(),
+                    });
+                }
+
+                continue;
+            }
+
+            let mut args = command.args.clone();
+
+            match command.command {
+                'M' | 'L' | 'C' | 'S' | 'Q' | 'T' => {
+                    for (i, arg) in args.iter_mut().enumerate() {
+                        *arg += if i % 2 == 0 { dx } else { dy };
+                    }
+                }
+                'H' => args.iter_mut().for_each(|arg| *arg += dx),
+                'V' => args.iter_mut().for_each(|arg| *arg += dy),
+                'A' => {
+                    for (i, arg) in args.iter_mut().enumerate() {
+                        match i % 7 {
+                            5 => *arg += dx,
+                            6 => *arg += dy,
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            commands.push(Command {
+                command: command.command,
+                args,
+            });
+        }
+
+        Path { commands }
+    }
+
+    /// Returns a copy of the path scaled by `factor` around the origin.
+    pub fn scale(&self, factor: f64) -> Path {
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| Command {
+                command: command.command,
+                args: command
+                    .args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| match command.command {
+                        // Arc rotation and flags aren't lengths.
+                        'A' | 'a' if (2..=4).contains(&(i % 7)) => *arg,
+                        _ => arg * factor,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Path { commands }
+    }
+
+    /// Appends the commands of `other` after the ones of this path.
+    pub fn append(&mut self, other: Path) {
+        self.commands.extend(other.commands);
+    }
+
+    /// The path as absolute drawing operations. Every subpath starts with a
+    /// `MoveTo`, arcs becoming cubic curves.
+    pub(crate) fn elements(&self) -> Vec<Element> {
+        self.elements_within(f64::INFINITY)
+    }
+
+    /// Same as `elements`, with arcs approximated within `tolerance`.
+    pub(crate) fn elements_within(&self, tolerance: f64) -> Vec<Element> {
+        let mut elements = Vec::new();
+        let mut current = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        let mut closed = true;
+        // Control points reflected by smooth cubic and quadratic curves.
+        let mut cubic_control = None;
+        let mut quad_control = None;
+
+        for command in &self.commands {
+            let relative = command.command.is_ascii_lowercase();
+            let kind = command.command.to_ascii_uppercase();
+
+            if kind == 'Z' {
+                if !closed {
+                    elements.push(Element::Close);
+                }
+
+                current = start;
+                closed = true;
+                cubic_control = None;
+                quad_control = None;
+                continue;
+            }
+
+            let arity = match kind {
+                'H' | 'V' => 1,
+                'M' | 'L' | 'T' => 2,
+                'S' | 'Q' => 4,
+                'C' => 6,
+                _ => 7,
+            };
+
+            for (i, args) in command.args.chunks_exact(arity).enumerate() {
+                let point = |x: f64, y: f64| {
+                    if relative {
+                        (current.0 + x, current.1 + y)
+                    } else {
+                        (x, y)
+                    }
+                };
+
+                if kind == 'M' && i == 0 {
+                    current = point(args[0], args[1]);
+                    start = current;
+                    closed = false;
+                    elements.push(Element::MoveTo(current));
+                    cubic_control = None;
+                    quad_control = None;
+                    continue;
+                }
+
+                // Drawing right after a closepath starts again from its start.
+                if closed {
+                    elements.push(Element::MoveTo(start));
+                    closed = false;
+                }
+
+                let (element, end) = match kind {
+                    'H' => {
+                        let end = point(args[0], 0.0);
+                        let end = (end.0, current.1);
+                        (Element::LineTo(end), end)
+                    }
+                    'V' => {
+                        let end = point(0.0, args[0]);
+                        let end = (current.0, end.1);
+                        (Element::LineTo(end), end)
+                    }
+                    'M' | 'L' => {
+                        let end = point(args[0], args[1]);
+                        (Element::LineTo(end), end)
+                    }
+                    'C' => {
+                        let end = point(args[4], args[5]);
+                        (
+                            Element::CubicTo(point(args[0], args[1]), point(args[2], args[3]), end),
+                            end,
+                        )
+                    }
+                    'S' => {
+                        let end = point(args[2], args[3]);
+                        let control = reflect(cubic_control, current);
+                        (Element::CubicTo(control, point(args[0], args[1]), end), end)
+                    }
+                    'Q' => {
+                        let end = point(args[2], args[3]);
+                        (Element::QuadTo(point(args[0], args[1]), end), end)
+                    }
+                    'T' => {
+                        let end = point(args[0], args[1]);
+                        (Element::QuadTo(reflect(quad_control, current), end), end)
+                    }
+                    // Arcs without radius are straight lines.
+                    _ if args[0] == 0.0 || args[1] == 0.0 => {
+                        let end = point(args[5], args[6]);
+                        (Element::LineTo(end), end)
+                    }
+                    _ => {
+                        let end = point(args[5], args[6]);
+
+                        for (c1, c2, to) in arc_to_cubics(
+                            current,
+                            (args[0], args[1]),
+                            args[2],
+                            args[3] != 0.0,
+                            args[4] != 0.0,
+                            end,
+                            tolerance,
+                        ) {
+                            elements.push(Element::CubicTo(c1, c2, to));
+                        }
+
+                        current = end;
+                        cubic_control = None;
+                        quad_control = None;
+                        continue;
+                    }
+                };
+
+                cubic_control = match element {
+                    Element::CubicTo(_, c2, _) => Some(c2),
+                    _ => None,
+                };
+                quad_control = match element {
+                    Element::QuadTo(c, _) => Some(c),
+                    _ => None,
+                };
+
+                elements.push(element);
+                current = end;
+            }
+        }
+
+        elements
+    }
+
     pub fn bounds(&self) -> Result<Bounds, String> {
         let mut bounds = Bounds::new();
         let mut is_first = true;
@@ -69,7 +455,12 @@ impl Path {
         for command in &self.commands {
             match command.command {
                 'M' => {
-                    bounds = bounds.move_last_point(command.args[0], command.args[1], is_first);
+                    // Later subpaths extend the bounds rather than replacing them.
+                    bounds = if is_first {
+                        bounds.move_last_point(command.args[0], command.args[1], true)
+                    } else {
+                        bounds.extends(command.args[0], command.args[1])
+                    };
                 }
                 'L' => {
                     bounds = bounds.extends(command.args[0], command.args[1]);
@@ -160,6 +551,277 @@ impl Path {
     }
 }
 
+/// Writes path data, rounding numbers to the formatter precision when one is
+/// given: `format!("{:.2}", path)`.
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in &self.commands {
+            write!(f, "{}", command.command)?;
+
+            for (i, arg) in command.args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " ")?;
+                }
+
+                match f.precision() {
+                    Some(precision) => write!(f, "{}", round(*arg, precision))?,
+                    None => write!(f, "{}", arg)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of arguments taken by each segment of a command.
+pub(crate) fn arity(command: char) -> usize {
+    match command.to_ascii_uppercase() {
+        'Z' => 0,
+        'H' | 'V' => 1,
+        'M' | 'L' | 'T' => 2,
+        'S' | 'Q' => 4,
+        'C' => 6,
+        _ => 7,
+    }
+}
+
+/// Turns commands into segments, following the current point which
+/// horizontal and vertical lines and smooth curves depend on.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SegmentReader {
+    current: Point,
+    start: Point,
+    // Absolute control points reflected by smooth curves.
+    cubic_control: Option<Point>,
+    quad_control: Option<Point>,
+}
+
+impl SegmentReader {
+    /// The segment drawn by `args`, a set of arguments of `command`. `first`
+    /// tells whether they're the first ones of the command.
+    pub(crate) fn read(&mut self, command: char, first: bool, args: &[f64]) -> Segment {
+        let relative = command.is_ascii_lowercase();
+        let current = self.current;
+        let absolute = |(x, y): Point| {
+            if relative {
+                (current.0 + x, current.1 + y)
+            } else {
+                (x, y)
+            }
+        };
+        let written = |(x, y): Point| {
+            if relative {
+                (x - current.0, y - current.1)
+            } else {
+                (x, y)
+            }
+        };
+
+        let segment = match command.to_ascii_uppercase() {
+            'Z' => Segment::Close,
+            'M' if first => Segment::MoveTo {
+                relative,
+                to: (args[0], args[1]),
+            },
+            'M' | 'L' => Segment::LineTo {
+                relative,
+                to: (args[0], args[1]),
+            },
+            'H' if relative => Segment::LineTo {
+                relative,
+                to: (args[0], 0.0),
+            },
+            'H' => Segment::LineTo {
+                relative,
+                to: (args[0], current.1),
+            },
+            'V' if relative => Segment::LineTo {
+                relative,
+                to: (0.0, args[0]),
+            },
+            'V' => Segment::LineTo {
+                relative,
+                to: (current.0, args[0]),
+            },
+            'C' => Segment::CubicTo {
+                relative,
+                control1: (args[0], args[1]),
+                control2: (args[2], args[3]),
+                to: (args[4], args[5]),
+            },
+            'S' => Segment::CubicTo {
+                relative,
+                control1: written(reflect(self.cubic_control, current)),
+                control2: (args[0], args[1]),
+                to: (args[2], args[3]),
+            },
+            'Q' => Segment::QuadTo {
+                relative,
+                control: (args[0], args[1]),
+                to: (args[2], args[3]),
+            },
+            'T' => Segment::QuadTo {
+                relative,
+                control: written(reflect(self.quad_control, current)),
+                to: (args[0], args[1]),
+            },
+            _ => Segment::ArcTo {
+                relative,
+                radii: (args[0], args[1]),
+                x_axis_rotation: args[2],
+                large_arc: args[3] != 0.0,
+                sweep: args[4] != 0.0,
+                to: (args[5], args[6]),
+            },
+        };
+
+        self.cubic_control = None;
+        self.quad_control = None;
+
+        match segment {
+            Segment::MoveTo { to, .. } => {
+                self.current = absolute(to);
+                self.start = self.current;
+            }
+            Segment::CubicTo { control2, to, .. } => {
+                self.cubic_control = Some(absolute(control2));
+                self.current = absolute(to);
+            }
+            Segment::QuadTo { control, to, .. } => {
+                self.quad_control = Some(absolute(control));
+                self.current = absolute(to);
+            }
+            Segment::LineTo { to, .. } | Segment::ArcTo { to, .. } => self.current = absolute(to),
+            Segment::Close => self.current = self.start,
+        }
+
+        segment
+    }
+}
+
+/// The reflection of a control point of the previous curve around `current`,
+/// or `current` when the previous segment isn't a curve of the same kind.
+fn reflect(control: Option<Point>, current: Point) -> Point {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
+
+/// Distance between a quarter of a unit circle and its cubic approximation.
+const QUARTER_ARC_ERROR: f64 = 0.00027;
+
+/// Approximates an elliptical arc with cubic curves of at most a quarter
+/// turn each, following the SVG endpoint to center parameterization. Radii
+/// must not be zero.
+///
+/// Quarters are split further until curves are within `tolerance` of the arc,
+/// the error decreasing with the sixth power of their angle.
+pub(crate) fn arc_to_cubics(
+    from: Point,
+    radii: Point,
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+    tolerance: f64,
+) -> Vec<(Point, Point, Point)> {
+    let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+
+    if from == to {
+        return Vec::new();
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let dx = (from.0 - to.0) / 2.0;
+    let dy = (from.1 - to.1) / 2.0;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Radii too small to reach the end point are scaled up.
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+    let cx = cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0;
+
+    let angle = |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+
+    let start = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle(
+        (x1 - cx1) / rx,
+        (y1 - cy1) / ry,
+        (-x1 - cx1) / rx,
+        (-y1 - cy1) / ry,
+    );
+
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * std::f64::consts::PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * std::f64::consts::PI;
+    }
+
+    let quarter_error = QUARTER_ARC_ERROR * rx.max(ry);
+    let splits = (quarter_error / tolerance).powf(1.0 / 6.0).ceil().max(1.0);
+    let count = ((delta.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.0) * splits) as usize;
+    let step = delta / count as f64;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+
+    let point = |theta: f64| {
+        let (sin_t, cos_t) = theta.sin_cos();
+        (
+            cx + rx * cos * cos_t - ry * sin * sin_t,
+            cy + rx * sin * cos_t + ry * cos * sin_t,
+        )
+    };
+    let derivative = |theta: f64| {
+        let (sin_t, cos_t) = theta.sin_cos();
+        (
+            -rx * cos * sin_t - ry * sin * cos_t,
+            -rx * sin * sin_t + ry * cos * cos_t,
+        )
+    };
+
+    (0..count)
+        .map(|i| {
+            let theta1 = start + step * i as f64;
+            let theta2 = theta1 + step;
+            let (p1, d1) = (point(theta1), derivative(theta1));
+            let (d2, end) = (derivative(theta2), point(theta2));
+
+            let end = if i == count - 1 { to } else { end };
+
+            (
+                (p1.0 + handle * d1.0, p1.1 + handle * d1.1),
+                (end.0 - handle * d2.0, end.1 - handle * d2.1),
+                end,
+            )
+        })
+        .collect()
+}
+
+/// Rounds `value` to `precision` decimals, without negative zeros.
+pub(crate) fn round(value: f64, precision: usize) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+
+    (value * factor).round() / factor + 0.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,7 +831,7 @@ mod tests {
     fn it_panic_when_having_invalid_svg_path_command() {
         let path = Path::new("M 0 0 W 10 10 20 20".to_string());
 
-        if let Err(_) = path {
+        if path.is_err() {
             panic!("Invalid path command");
         }
     }
@@ -401,4 +1063,68 @@ mod tests {
             "The last point should be the first one"
         );
     }
+
+    #[test]
+    fn it_serializes_a_path() {
+        let path = Path::new("M 10 20 l 65 80 H 5 z".to_string()).unwrap();
+
+        assert_eq!(path.to_string(), "M10 20l65 80H5z");
+    }
+
+    #[test]
+    fn it_serializes_a_path_with_a_precision() {
+        let path = Path::new("M 10.25 -0.004 l 65.126 80 z".to_string()).unwrap();
+
+        assert_eq!(format!("{:.1}", path), "M10.3 0l65.1 80z");
+    }
+
+    #[test]
+    fn it_translates_absolute_commands_only() {
+        let path = Path::new("M 10 20 l 65 80 H 5 V 7 A 5 5 0 0 1 1 2 z".to_string()).unwrap();
+
+        assert_eq!(
+            path.translate(100.0, 10.0).to_string(),
+            "M110 30l65 80H105V17A5 5 0 0 1 101 12z"
+        );
+    }
+
+    #[test]
+    fn it_translates_a_leading_relative_move() {
+        let path = Path::new("m 10 20 5 5".to_string()).unwrap();
+
+        assert_eq!(path.translate(1.0, 1.0).to_string(), "M11 21l5 5");
+    }
+
+    #[test]
+    fn it_scales_a_path() {
+        let path = Path::new("M 10 20 h 5 A 5 5 30 0 1 1 2".to_string()).unwrap();
+
+        assert_eq!(path.scale(2.0).to_string(), "M20 40h10A10 10 30 0 1 2 4");
+    }
+
+    #[test]
+    fn it_extends_the_bounds_with_every_subpath() {
+        let path = Path::new("M 10 20 L 50 50 Z M 0 5 L 5 100 Z".to_string()).unwrap();
+
+        let bounds = path.bounds().unwrap();
+
+        assert_eq!(bounds.x1(), 0.0);
+        assert_eq!(bounds.y1(), 5.0);
+        assert_eq!(bounds.x2(), 50.0);
+        assert_eq!(bounds.y2(), 100.0);
+    }
+
+    #[test]
+    fn it_includes_every_contour_of_a_glyph_in_its_bounds() {
+        // The dot of an Arial "i" is its second contour.
+        let path = Path::new("M328 0h-189v1061h189v-1061zM328 1262h-189v204h189v-204z".to_string())
+            .unwrap();
+
+        let bounds = path.bounds().unwrap();
+
+        assert_eq!(
+            (bounds.x1(), bounds.y1(), bounds.x2(), bounds.y2()),
+            (139.0, 0.0, 328.0, 1466.0)
+        );
+    }
 }
@@ -0,0 +1,316 @@
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+use super::arabic::{joinings, presentation_form, ArabicForm, Joining};
+use super::font::{Font, Glyph};
+use super::layout::Orientation;
+use super::path::Path;
+
+/// Glyphs chosen for `length` consecutive characters starting at `start`: a
+/// base glyph and the combining marks drawn over it.
+pub(crate) struct ShapedGlyph<'a> {
+    pub(crate) start: usize,
+    pub(crate) length: usize,
+    pub(crate) glyph: Option<&'a Glyph>,
+    pub(crate) marks: Vec<&'a Glyph>,
+    /// Kerning with the previous and next base glyphs in logical order, as
+    /// SVG `k` values.
+    pub(crate) kerning_before: f64,
+    pub(crate) kerning_after: f64,
+}
+
+impl ShapedGlyph<'_> {
+    pub(crate) fn horizontal_advance_x(&self) -> f64 {
+        match self.glyph {
+            Some(glyph) => glyph.horizontal_advance_x,
+            None => 0.0,
+        }
+    }
+
+    /// The outline of the base glyph with its marks positioned over it.
+    pub(crate) fn path(&self, units_per_em: f64) -> String {
+        let mut path = match self.glyph {
+            Some(glyph) => glyph.path.clone(),
+            None => String::new(),
+        };
+
+        if self.marks.is_empty() {
+            return path;
+        }
+
+        let base = self.glyph.and_then(|glyph| glyph_bounds(&glyph.path));
+        let gap = units_per_em * MARK_GAP;
+
+        let (center, mut top, mut bottom) = match base {
+            Some((x1, y1, x2, y2)) => ((x1 + x2) / 2.0, y2, y1),
+            None => (0.0, 0.0, 0.0),
+        };
+
+        for mark in &self.marks {
+            let (x1, y1, x2, y2) = match glyph_bounds(&mark.path) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+            let dx = if base.is_some() {
+                center - (x1 + x2) / 2.0
+            } else {
+                0.0
+            };
+
+            // Marks are designed around a lowercase base: only move them when
+            // they would collide with the base or with a previous mark.
+            let dy = if y1 + y2 >= 0.0 {
+                let dy = (top + gap - y1).max(0.0);
+                top = y2 + dy;
+                dy
+            } else {
+                let dy = (bottom - gap - y2).min(0.0);
+                bottom = y1 + dy;
+                dy
+            };
+
+            if let Ok(mark_path) = Path::new(mark.path.clone()) {
+                path.push_str(&mark_path.translate(dx, dy).to_string());
+            }
+        }
+
+        path
+    }
+}
+
+/// Space kept between a base glyph and a mark moved over it, in em.
+const MARK_GAP: f64 = 0.05;
+
+impl Font {
+    /// Picks glyphs for a sequence of characters given in logical order,
+    /// selecting Arabic contextual forms and ligatures. As in SVG fonts, the
+    /// first glyph in document order matching the text wins.
+    ///
+    /// Combining sequences use their precomposed (NFC) glyph when the font
+    /// has one, and are otherwise decomposed (NFD) into a base glyph with
+    /// marks positioned over it.
+    pub(crate) fn shape(
+        &self,
+        characters: &[char],
+        lang: Option<&str>,
+        orientation: Orientation,
+    ) -> Vec<ShapedGlyph<'_>> {
+        let joinings = joinings(characters);
+        let forms = joinings
+            .iter()
+            .map(|joining| joining.map(|j| j.form()))
+            .collect::<Vec<Option<ArabicForm>>>();
+
+        let glyph_for = |character: char, form: Option<ArabicForm>| {
+            self.glyph_for_form(character, form, lang, orientation)
+        };
+
+        let mut shaped = Vec::new();
+        let mut start = 0;
+
+        while start < characters.len() {
+            let mut end = start + 1;
+
+            while end < characters.len() && canonical_combining_class(characters[end]) != 0 {
+                end += 1;
+            }
+
+            let composed = characters[start..end]
+                .iter()
+                .copied()
+                .nfc()
+                .collect::<Vec<char>>();
+
+            let matched = self.glyph_at(characters, &joinings, &forms, start, lang, orientation);
+            let precomposed = match composed.len() {
+                1 if end - start > 1 => glyph_for(composed[0], forms[start]),
+                _ => None,
+            };
+
+            let (glyph, length, mut marks) = match (matched, precomposed) {
+                (Some(glyph), _) if glyph.characters.len() >= end - start => {
+                    (Some(glyph), glyph.characters.len(), Vec::new())
+                }
+                (_, Some(glyph)) => (Some(glyph), end - start, Vec::new()),
+                (Some(glyph), None) => (Some(glyph), glyph.characters.len(), Vec::new()),
+                (None, None) => match glyph_for(characters[start], forms[start]) {
+                    Some(glyph) => (Some(glyph), 1, Vec::new()),
+                    None => {
+                        let decomposed = std::iter::once(characters[start])
+                            .nfd()
+                            .collect::<Vec<char>>();
+
+                        let marks = decomposed[1..]
+                            .iter()
+                            .filter_map(|mark| glyph_for(*mark, None))
+                            .collect::<Vec<&Glyph>>();
+
+                        (glyph_for(decomposed[0], forms[start]), 1, marks)
+                    }
+                },
+            };
+
+            // Marks left in the combining sequence are drawn over the base.
+            let mut position = start + length;
+
+            while position < end {
+                match self.glyph_at(characters, &joinings, &forms, position, lang, orientation) {
+                    Some(glyph) if position + glyph.characters.len() <= end => {
+                        marks.push(glyph);
+                        position += glyph.characters.len();
+                    }
+                    _ => {
+                        if let Some(mark) = glyph_for(characters[position], forms[position]) {
+                            marks.push(mark);
+                        }
+                        position += 1;
+                    }
+                }
+            }
+
+            shaped.push(ShapedGlyph {
+                start,
+                length: position - start,
+                glyph,
+                marks,
+                kerning_before: 0.0,
+                kerning_after: 0.0,
+            });
+
+            start = position;
+        }
+
+        for i in 1..shaped.len() {
+            if let (Some(left), Some(right)) = (shaped[i - 1].glyph, shaped[i].glyph) {
+                let k = self.kerning_between(left, right);
+
+                shaped[i - 1].kerning_after = k;
+                shaped[i].kerning_before = k;
+            }
+        }
+
+        shaped
+    }
+
+    /// Finds the first glyph in document order, ligatures included, whose
+    /// characters match the ones at `start`, either as they are or through
+    /// their Arabic presentation forms, in the right language, orientation
+    /// and contextual form.
+    fn glyph_at(
+        &self,
+        characters: &[char],
+        joinings: &[Option<Joining>],
+        forms: &[Option<ArabicForm>],
+        start: usize,
+        lang: Option<&str>,
+        orientation: Orientation,
+    ) -> Option<&Glyph> {
+        self.glyphs().iter().find(|g| {
+            let end = start + g.characters.len();
+
+            if g.characters.is_empty() || end > characters.len() || !g.matches(lang, orientation) {
+                return false;
+            }
+
+            let same_characters = g.characters.iter().enumerate().all(|(i, c)| {
+                let character = characters[start + i];
+
+                *c == character
+                    || forms[start + i].and_then(|f| presentation_form(character, f)) == Some(*c)
+            });
+
+            let previous = joinings[start].is_some_and(|j| j.previous);
+            let next = joinings[end - 1].is_some_and(|j| j.next);
+
+            same_characters
+                && match g.arabic_form {
+                    Some(form) => form == (Joining { previous, next }).form(),
+                    None => true,
+                }
+        })
+    }
+}
+
+fn glyph_bounds(path: &str) -> Option<(f64, f64, f64, f64)> {
+    if path.is_empty() {
+        return None;
+    }
+
+    let bounds = Path::new(path.to_string()).ok()?.bounds().ok()?;
+
+    Some((bounds.x1(), bounds.y1(), bounds.x2(), bounds.y2()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::layout::TextOptions;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_uses_the_precomposed_glyph_for_decomposed_input() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        let decomposed = font.layout("e\u{301}te\u{301}", &TextOptions::default());
+        let precomposed = font.layout("été", &TextOptions::default());
+
+        assert_eq!(decomposed.len(), 3);
+        assert_eq!(decomposed[0].text, "e\u{301}");
+        assert_eq!(decomposed[2].index, 4);
+        assert_eq!(decomposed[0].path, precomposed[0].path);
+        assert_eq!(
+            font.text_width("e\u{301}te\u{301}".to_string()),
+            font.text_width("été".to_string())
+        );
+    }
+
+    #[test]
+    fn it_positions_marks_over_the_base_glyph() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="a" d="M0 0h400v400h-400z" />
+                    <glyph unicode="A" d="M0 0h400v700h-400z" />
+                    <glyph unicode="&#x301;" horiz-adv-x="300" d="M0 500h100v100h-100z" />
+                </font>
+            </svg>"#;
+        let font = Font::new(data.to_string()).unwrap();
+
+        let lowercase = font.layout("a\u{301}", &TextOptions::default());
+        let uppercase = font.layout("\u{c1}", &TextOptions::default());
+
+        assert_eq!(lowercase.len(), 1);
+        assert_eq!(lowercase[0].advance, 500.0);
+        assert_eq!(
+            lowercase[0].path,
+            "M0 0h400v400h-400zM150 500h100v100h-100z"
+        );
+        assert_eq!(
+            uppercase[0].path,
+            "M0 0h400v700h-400zM150 750h100v100h-100z"
+        );
+    }
+
+    #[test]
+    fn it_uses_the_first_matching_glyph_in_document_order() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="f" horiz-adv-x="300" />
+                    <glyph unicode="i" horiz-adv-x="200" />
+                    <glyph unicode="fi" horiz-adv-x="450" />
+                    <glyph unicode="ffl" horiz-adv-x="600" />
+                    <glyph unicode="l" horiz-adv-x="250" />
+                </font>
+            </svg>"#;
+        let font = Font::new(data.to_string()).unwrap();
+
+        // The ligatures come after the glyph of their first character.
+        assert_eq!(font.layout("fi", &TextOptions::default()).len(), 2);
+        assert_eq!(font.text_width("fi".to_string()), 500.0);
+        assert_eq!(font.text_width("ffl".to_string()), 850.0);
+    }
+}
@@ -0,0 +1,576 @@
+use super::flatten::lerp;
+use super::hit::segment_distance;
+use super::path::{round, Path, Point, Segment};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimplifyReport {
+    pub segments_before: usize,
+    pub segments_after: usize,
+    /// Lengths of the serialized path data.
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+impl SimplifyReport {
+    /// The share of the serialized size saved, between 0 and 1.
+    pub fn reduction(&self) -> f64 {
+        if self.bytes_before == 0 {
+            return 0.0;
+        }
+
+        1.0 - self.bytes_after as f64 / self.bytes_before as f64
+    }
+}
+
+/// Lines in a row from which fitting cubic curves is worth trying.
+const MIN_FIT_LINES: usize = 4;
+
+/// Newton iterations refining the parameters of fitted points.
+const REPARAMETERIZATIONS: usize = 4;
+
+impl Path {
+    /// A smaller path staying within `tolerance` of this one. Zero length
+    /// segments are removed, straight curves and collinear lines become single
+    /// lines, cubic curves are fitted to dense polylines and coordinates are
+    /// rounded to as few decimals as the tolerance allows.
+    ///
+    /// Half of the tolerance goes to the simplification and half to rounding.
+    pub fn simplify(&self, tolerance: f64) -> Path {
+        let tolerance = tolerance.abs() / 2.0;
+        let precision = (0..15)
+            .find(|p| std::f64::consts::SQRT_2 * 0.5 * 10f64.powi(-p) <= tolerance)
+            .unwrap_or(15) as usize;
+
+        let mut segments = Vec::new();
+
+        for subpath in self.subpaths() {
+            let simplified = simplify_subpath(subpath.segments(), tolerance);
+            let mut current = (0.0, 0.0);
+
+            for segment in simplified {
+                let segment = round_segment(segment, precision);
+
+                // Rounding may bring points together.
+                match segment {
+                    Segment::MoveTo { to, .. } => current = to,
+                    Segment::LineTo { to, .. } | Segment::ArcTo { to, .. } if to == current => {
+                        continue
+                    }
+                    Segment::LineTo { to, .. }
+                    | Segment::QuadTo { to, .. }
+                    | Segment::CubicTo { to, .. }
+                    | Segment::ArcTo { to, .. } => current = to,
+                    Segment::Close => {}
+                }
+
+                segments.push(segment);
+            }
+        }
+
+        Path::from_segments(&segments)
+    }
+
+    /// Compares the path with its simplified version.
+    pub fn simplify_report(&self, simplified: &Path) -> SimplifyReport {
+        SimplifyReport {
+            segments_before: self.segments().count(),
+            segments_after: simplified.segments().count(),
+            bytes_before: self.to_string().len(),
+            bytes_after: simplified.to_string().len(),
+        }
+    }
+}
+
+/// Simplifies the absolute segments of a subpath, leaving rounding aside.
+fn simplify_subpath<I: Iterator<Item = Segment>>(segments: I, tolerance: f64) -> Vec<Segment> {
+    let mut simplified = Vec::new();
+    // Points of the lines drawn in a row, from the point they start at.
+    let mut lines = Vec::new();
+    let mut start = (0.0, 0.0);
+    let mut current = (0.0, 0.0);
+
+    for segment in segments {
+        let to = match segment {
+            Segment::MoveTo { to, .. } => {
+                start = to;
+                current = to;
+                simplified.push(segment);
+                continue;
+            }
+            Segment::LineTo { to, .. } => to,
+            Segment::QuadTo { control, to, .. }
+                if is_straight(current, &[control], to, tolerance) =>
+            {
+                to
+            }
+            Segment::CubicTo {
+                control1,
+                control2,
+                to,
+                ..
+            } if is_straight(current, &[control1, control2], to, tolerance) => to,
+            Segment::Close => {
+                // The closepath draws the line back to the start anyway.
+                if lines.len() > 2 && lines.last() == Some(&start) {
+                    lines.pop();
+                }
+
+                simplified.extend(simplify_lines(&lines, tolerance));
+                simplified.push(segment);
+                lines.clear();
+                current = start;
+                continue;
+            }
+            Segment::QuadTo { to, .. }
+            | Segment::CubicTo { to, .. }
+            | Segment::ArcTo { to, .. } => {
+                simplified.extend(simplify_lines(&lines, tolerance));
+                lines.clear();
+
+                if to != current {
+                    simplified.push(segment);
+                    current = to;
+                }
+
+                continue;
+            }
+        };
+
+        if to == current {
+            continue;
+        }
+
+        if lines.is_empty() {
+            lines.push(current);
+        }
+
+        lines.push(to);
+        current = to;
+    }
+
+    simplified.extend(simplify_lines(&lines, tolerance));
+    simplified
+}
+
+/// Whether a curve stays within `tolerance` of its chord, its control points
+/// being close enough to it.
+fn is_straight(from: Point, controls: &[Point], to: Point, tolerance: f64) -> bool {
+    controls
+        .iter()
+        .all(|control| segment_distance(*control, from, to) <= tolerance)
+}
+
+/// Replaces the lines joining `points` with fewer lines, or with cubic curves
+/// when it takes fewer numbers.
+fn simplify_lines(points: &[Point], tolerance: f64) -> Vec<Segment> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let merged = merge_collinear(points, tolerance);
+    let lines = merged[1..]
+        .iter()
+        .map(|to| Segment::LineTo {
+            relative: false,
+            to: *to,
+        })
+        .collect::<Vec<Segment>>();
+
+    if points.len() - 1 < MIN_FIT_LINES {
+        return lines;
+    }
+
+    let first = normalize((points[1].0 - points[0].0, points[1].1 - points[0].1));
+    let n = points.len() - 1;
+    let last = normalize((points[n - 1].0 - points[n].0, points[n - 1].1 - points[n].1));
+
+    let mut curves = Vec::new();
+    fit_cubics(points, first, last, tolerance, &mut curves);
+
+    if 6 * curves.len() < 2 * lines.len() {
+        curves
+    } else {
+        lines
+    }
+}
+
+/// Keeps the points which can't be skipped without the polyline moving by
+/// more than `tolerance`.
+fn merge_collinear(points: &[Point], tolerance: f64) -> Vec<Point> {
+    let mut merged = vec![points[0]];
+    let mut anchor = 0;
+
+    while anchor < points.len() - 1 {
+        let mut end = anchor + 1;
+
+        while end + 1 < points.len()
+            && points[anchor + 1..end + 1]
+                .iter()
+                .all(|point| segment_distance(*point, points[anchor], points[end + 1]) <= tolerance)
+        {
+            end += 1;
+        }
+
+        merged.push(points[end]);
+        anchor = end;
+    }
+
+    merged
+}
+
+/// Fits cubic curves to `points`, splitting them where a single curve is too
+/// far from them. `first` and `last` are the unit tangents at both ends,
+/// pointing inwards.
+fn fit_cubics(
+    points: &[Point],
+    first: Point,
+    last: Point,
+    tolerance: f64,
+    curves: &mut Vec<Segment>,
+) {
+    let n = points.len() - 1;
+
+    // A straight line is a cubic curve too.
+    if n == 1 {
+        curves.push(Segment::CubicTo {
+            relative: false,
+            control1: lerp(points[0], points[1], 1.0 / 3.0),
+            control2: lerp(points[0], points[1], 2.0 / 3.0),
+            to: points[1],
+        });
+        return;
+    }
+
+    let mut parameters = chord_parameters(points);
+    let mut curve = fit_cubic(points, &parameters, first, last);
+    let (mut error, mut split) = fit_error(points, &parameters, &curve);
+
+    for _ in 0..REPARAMETERIZATIONS {
+        if error <= tolerance {
+            break;
+        }
+
+        for (u, point) in parameters.iter_mut().zip(points) {
+            *u = newton(&curve, *point, *u);
+        }
+
+        curve = fit_cubic(points, &parameters, first, last);
+        (error, split) = fit_error(points, &parameters, &curve);
+    }
+
+    if error <= tolerance {
+        curves.push(Segment::CubicTo {
+            relative: false,
+            control1: curve[1],
+            control2: curve[2],
+            to: curve[3],
+        });
+        return;
+    }
+
+    let split = split.clamp(1, n - 1);
+    let center = normalize((
+        points[split - 1].0 - points[split + 1].0,
+        points[split - 1].1 - points[split + 1].1,
+    ));
+
+    fit_cubics(&points[..=split], first, center, tolerance, curves);
+    fit_cubics(
+        &points[split..],
+        (-center.0, -center.1),
+        last,
+        tolerance,
+        curves,
+    );
+}
+
+/// Parameters of the points proportional to the distance along them.
+fn chord_parameters(points: &[Point]) -> Vec<f64> {
+    let mut parameters = vec![0.0];
+
+    for i in 1..points.len() {
+        let length = (points[i].0 - points[i - 1].0).hypot(points[i].1 - points[i - 1].1);
+        parameters.push(parameters[i - 1] + length);
+    }
+
+    let total = parameters[points.len() - 1];
+
+    parameters.iter().map(|u| u / total).collect()
+}
+
+/// The cubic curve through the ends of `points` along the tangents which is
+/// the closest to them in the least squares sense.
+fn fit_cubic(points: &[Point], parameters: &[f64], first: Point, last: Point) -> [Point; 4] {
+    let (p0, p3) = (points[0], points[points.len() - 1]);
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+
+    for (point, u) in points.iter().zip(parameters) {
+        let v = 1.0 - u;
+        let (b0, b1, b2, b3) = (v * v * v, 3.0 * u * v * v, 3.0 * u * u * v, u * u * u);
+        let a0 = (first.0 * b1, first.1 * b1);
+        let a1 = (last.0 * b2, last.1 * b2);
+        let rest = (
+            point.0 - p0.0 * (b0 + b1) - p3.0 * (b2 + b3),
+            point.1 - p0.1 * (b0 + b1) - p3.1 * (b2 + b3),
+        );
+
+        c[0][0] += dot(a0, a0);
+        c[0][1] += dot(a0, a1);
+        c[1][1] += dot(a1, a1);
+        x[0] += dot(a0, rest);
+        x[1] += dot(a1, rest);
+    }
+
+    let determinant = c[0][0] * c[1][1] - c[0][1] * c[0][1];
+    let chord = (p3.0 - p0.0).hypot(p3.1 - p0.1);
+    let (mut alpha0, mut alpha1) = if determinant.abs() > f64::EPSILON {
+        (
+            (x[0] * c[1][1] - x[1] * c[0][1]) / determinant,
+            (c[0][0] * x[1] - c[0][1] * x[0]) / determinant,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    // Control points behind the ends make loops, a third of the chord is a
+    // safe guess then.
+    if alpha0 < chord * 1e-6 || alpha1 < chord * 1e-6 {
+        alpha0 = chord / 3.0;
+        alpha1 = chord / 3.0;
+    }
+
+    [
+        p0,
+        (p0.0 + first.0 * alpha0, p0.1 + first.1 * alpha0),
+        (p3.0 + last.0 * alpha1, p3.1 + last.1 * alpha1),
+        p3,
+    ]
+}
+
+/// The largest distance between the curve and the polyline, checked at the
+/// points and between them, with the index of the farthest point.
+fn fit_error(points: &[Point], parameters: &[f64], curve: &[Point; 4]) -> (f64, usize) {
+    let mut error = 0.0;
+    let mut split = points.len() / 2;
+
+    for i in 0..points.len() {
+        let (x, y) = evaluate(curve, parameters[i]);
+        let distance = (x - points[i].0).hypot(y - points[i].1);
+
+        if distance > error {
+            error = distance;
+            split = i;
+        }
+
+        if i + 1 < points.len() {
+            let middle = evaluate(curve, (parameters[i] + parameters[i + 1]) / 2.0);
+            let distance = segment_distance(middle, points[i], points[i + 1]);
+
+            if distance > error {
+                error = distance;
+                split = i + 1;
+            }
+        }
+    }
+
+    (error, split)
+}
+
+/// A parameter of the curve closer to `point`, after a Newton step.
+fn newton(curve: &[Point; 4], point: Point, u: f64) -> f64 {
+    let (x, y) = evaluate(curve, u);
+    let d1 = derivative(curve, u);
+    let d2 = second_derivative(curve, u);
+    let difference = (x - point.0, y - point.1);
+    let denominator = dot(d1, d1) + dot(difference, d2);
+
+    if denominator == 0.0 {
+        return u;
+    }
+
+    (u - dot(difference, d1) / denominator).clamp(0.0, 1.0)
+}
+
+fn evaluate(curve: &[Point; 4], u: f64) -> Point {
+    let v = 1.0 - u;
+    let (b0, b1, b2, b3) = (v * v * v, 3.0 * u * v * v, 3.0 * u * u * v, u * u * u);
+
+    (
+        b0 * curve[0].0 + b1 * curve[1].0 + b2 * curve[2].0 + b3 * curve[3].0,
+        b0 * curve[0].1 + b1 * curve[1].1 + b2 * curve[2].1 + b3 * curve[3].1,
+    )
+}
+
+fn derivative(curve: &[Point; 4], u: f64) -> Point {
+    let v = 1.0 - u;
+    let (b0, b1, b2) = (3.0 * v * v, 6.0 * u * v, 3.0 * u * u);
+
+    (
+        b0 * (curve[1].0 - curve[0].0)
+            + b1 * (curve[2].0 - curve[1].0)
+            + b2 * (curve[3].0 - curve[2].0),
+        b0 * (curve[1].1 - curve[0].1)
+            + b1 * (curve[2].1 - curve[1].1)
+            + b2 * (curve[3].1 - curve[2].1),
+    )
+}
+
+fn second_derivative(curve: &[Point; 4], u: f64) -> Point {
+    let (b0, b1) = (6.0 * (1.0 - u), 6.0 * u);
+
+    (
+        b0 * (curve[2].0 - 2.0 * curve[1].0 + curve[0].0)
+            + b1 * (curve[3].0 - 2.0 * curve[2].0 + curve[1].0),
+        b0 * (curve[2].1 - 2.0 * curve[1].1 + curve[0].1)
+            + b1 * (curve[3].1 - 2.0 * curve[2].1 + curve[1].1),
+    )
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn normalize((x, y): Point) -> Point {
+    let length = x.hypot(y);
+
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (x / length, y / length)
+    }
+}
+
+fn round_segment(segment: Segment, precision: usize) -> Segment {
+    let point = |(x, y): Point| (round(x, precision), round(y, precision));
+
+    match segment {
+        Segment::MoveTo { relative, to } => Segment::MoveTo {
+            relative,
+            to: point(to),
+        },
+        Segment::LineTo { relative, to } => Segment::LineTo {
+            relative,
+            to: point(to),
+        },
+        Segment::QuadTo {
+            relative,
+            control,
+            to,
+        } => Segment::QuadTo {
+            relative,
+            control: point(control),
+            to: point(to),
+        },
+        Segment::CubicTo {
+            relative,
+            control1,
+            control2,
+            to,
+        } => Segment::CubicTo {
+            relative,
+            control1: point(control1),
+            control2: point(control2),
+            to: point(to),
+        },
+        Segment::ArcTo {
+            relative,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+        } => Segment::ArcTo {
+            relative,
+            radii: point(radii),
+            x_axis_rotation: round(x_axis_rotation, precision),
+            large_arc,
+            sweep,
+            to: point(to),
+        },
+        Segment::Close => Segment::Close,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::{path, ARIAL_SVG_FONT};
+
+    /// The largest distance from the points of `a` to `b`, both flattened
+    /// within 0.05.
+    fn distance(a: &Path, b: &Path) -> f64 {
+        let edges = b
+            .flatten(0.05)
+            .flat_map(|polyline| {
+                let points = polyline.points;
+                let count = points.len();
+
+                (0..count).map(move |i| (points[i], points[(i + 1) % count]))
+            })
+            .collect::<Vec<(Point, Point)>>();
+
+        a.flatten(0.05)
+            .flat_map(|polyline| polyline.points)
+            .map(|point| {
+                edges
+                    .iter()
+                    .map(|(from, to)| segment_distance(point, *from, *to))
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn it_merges_lines_and_removes_empty_segments() {
+        let path = path("M0 0 L5 0 L5 0 L10 0.001 L20 0 C20 3 20 7 20 10 L0 0 Z");
+
+        assert_eq!(path.simplify(0.1).to_string(), "M0 0L20 0L20 10Z");
+        assert_eq!(
+            self::path("M0.123456 0.987654 L10.5556 3.3333")
+                .simplify(0.01)
+                .to_string(),
+            "M0.123 0.988L10.556 3.333"
+        );
+    }
+
+    #[test]
+    fn it_fits_curves_to_dense_polylines() {
+        let mut d = String::from("M100 0");
+
+        for i in 1..200 {
+            let angle = i as f64 / 200.0 * std::f64::consts::TAU;
+            d.push_str(&format!(
+                " L{} {}",
+                100.0 * angle.cos(),
+                100.0 * angle.sin()
+            ));
+        }
+
+        d.push_str(" Z");
+
+        let polygon = path(&d);
+        let simplified = polygon.simplify(0.5);
+        let report = polygon.simplify_report(&simplified);
+
+        assert!(report.segments_after < 12);
+        assert!(report.reduction() > 0.8);
+        assert!(distance(&polygon, &simplified) <= 0.55);
+        assert!(distance(&simplified, &polygon) <= 0.55);
+    }
+
+    #[test]
+    fn it_keeps_glyphs_within_the_tolerance() {
+        let font = crate::svg::font::Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        for glyph in font.glyphs().iter().filter(|g| !g.path.is_empty()).take(40) {
+            let outline = path(&glyph.path);
+            let simplified = outline.simplify(1.0);
+            let report = outline.simplify_report(&simplified);
+
+            assert!(report.segments_after <= report.segments_before);
+            assert!(distance(&outline, &simplified) <= 1.05);
+            assert!(distance(&simplified, &outline) <= 1.05);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path as FilePath;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use flate2::read::GzDecoder;
+
+use super::font::Font;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+const UTF16_BE_BOM: [u8; 2] = [0xfe, 0xff];
+/// Signatures of TrueType, OpenType and font collection files.
+const OPENTYPE_MAGICS: [[u8; 4]; 4] = [*b"\0\x01\0\0", *b"OTTO", *b"true", *b"ttcf"];
+
+/// Media types accepted in data URIs, as found in CSS `@font-face` sources.
+const SVG_MEDIA_TYPES: [&str; 4] = [
+    "image/svg+xml",
+    "font/svg",
+    "font/svg+xml",
+    "application/font-svg",
+];
+
+impl Font {
+    pub fn from_svg(svg: &str) -> Result<Self, String> {
+        Font::new(svg.to_string())
+    }
+
+    /// Loads a font from raw bytes, which may be gzip compressed (`.svgz`)
+    /// and encoded in UTF-8 or, when starting with a byte order mark, UTF-16.
+    /// TrueType and OpenType fonts are loaded with `from_opentype`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if OPENTYPE_MAGICS.iter().any(|magic| bytes.starts_with(magic)) {
+            return Font::from_opentype(bytes);
+        }
+
+        if bytes.starts_with(&GZIP_MAGIC) {
+            let mut decompressed = Vec::new();
+
+            if let Err(error) = GzDecoder::new(bytes).read_to_end(&mut decompressed) {
+                return Err(format!("Invalid gzip data: {}", error));
+            }
+
+            return Font::from_bytes(&decompressed);
+        }
+
+        Font::new(decode_text(bytes)?)
+    }
+
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, String> {
+        let mut bytes = Vec::new();
+
+        if let Err(error) = reader.read_to_end(&mut bytes) {
+            return Err(format!("Unable to read font: {}", error));
+        }
+
+        Font::from_bytes(&bytes)
+    }
+
+    pub fn from_file<P: AsRef<FilePath>>(path: P) -> Result<Self, String> {
+        match fs::read(path.as_ref()) {
+            Ok(bytes) => Font::from_bytes(&bytes),
+            Err(error) => Err(format!(
+                "Unable to read {}: {}",
+                path.as_ref().display(),
+                error
+            )),
+        }
+    }
+
+    /// Loads a font from a `data:` URI such as `data:font/svg;base64,...`,
+    /// optionally wrapped in a CSS `url(...)`.
+    pub fn from_data_uri(uri: &str) -> Result<Self, String> {
+        let uri = unwrap_css_url(uri.trim());
+
+        let rest = match uri.get(..5) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("data:") => &uri[5..],
+            _ => return Err("Not a data URI".to_string()),
+        };
+
+        let (header, data) = match rest.split_once(',') {
+            Some(parts) => parts,
+            None => return Err("Invalid data URI: missing ','".to_string()),
+        };
+
+        let mut parameters = header.split(';').map(|p| p.trim());
+        let media_type = parameters.next().unwrap_or("").to_lowercase();
+        let is_base64 = parameters.any(|p| p.eq_ignore_ascii_case("base64"));
+
+        if !media_type.is_empty() && !SVG_MEDIA_TYPES.contains(&media_type.as_str()) {
+            return Err(format!("Unsupported data URI media type: {}", media_type));
+        }
+
+        let bytes = if is_base64 {
+            let data = data
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+
+            match STANDARD.decode(percent_decode(&data)?) {
+                Ok(bytes) => bytes,
+                Err(error) => return Err(format!("Invalid base64 data: {}", error)),
+            }
+        } else {
+            percent_decode(data)?
+        };
+
+        Font::from_bytes(&bytes)
+    }
+}
+
+impl FromStr for Font {
+    type Err = String;
+
+    fn from_str(svg: &str) -> Result<Self, Self::Err> {
+        Font::from_svg(svg)
+    }
+}
+
+fn decode_text(bytes: &[u8]) -> Result<String, String> {
+    if let Some(bytes) = bytes.strip_prefix(&UTF8_BOM) {
+        return decode_utf8(bytes);
+    }
+
+    let from_bytes: fn([u8; 2]) -> u16 = if bytes.starts_with(&UTF16_LE_BOM) {
+        u16::from_le_bytes
+    } else if bytes.starts_with(&UTF16_BE_BOM) {
+        u16::from_be_bytes
+    } else {
+        return decode_utf8(bytes);
+    };
+
+    let units = bytes[2..]
+        .chunks(2)
+        .map(|chunk| match chunk {
+            [a, b] => Ok(from_bytes([*a, *b])),
+            _ => Err("Invalid UTF-16 data: odd number of bytes".to_string()),
+        })
+        .collect::<Result<Vec<u16>, String>>()?;
+
+    match String::from_utf16(&units) {
+        Ok(text) => Ok(text),
+        Err(error) => Err(format!("Invalid UTF-16 data: {}", error)),
+    }
+}
+
+fn decode_utf8(bytes: &[u8]) -> Result<String, String> {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(text) => Ok(text),
+        Err(error) => Err(format!("Invalid UTF-8 data: {}", error)),
+    }
+}
+
+fn percent_decode(data: &str) -> Result<Vec<u8>, String> {
+    let bytes = data.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = data
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+            match byte {
+                Some(byte) => decoded.push(byte),
+                None => return Err("Invalid percent-encoding in data URI".to_string()),
+            }
+
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    Ok(decoded)
+}
+
+fn unwrap_css_url(value: &str) -> &str {
+    let value = match value.get(..4) {
+        Some(function) if function.eq_ignore_ascii_case("url(") && value.ends_with(')') => {
+            value[4..value.len() - 1].trim()
+        }
+        _ => value,
+    };
+
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    const FONT: &str = r#"<svg xmlns="http://www.w3.org/2000/svg">
+        <font id="font" horiz-adv-x="500">
+            <font-face units-per-em="1000" ascent="800" descent="-200" />
+            <glyph unicode="é" horiz-adv-x="600" d="M0 0h500v500h-500z" />
+        </font>
+    </svg>"#;
+
+    /// A file in the temporary directory, unique to the process and the
+    /// test, removed when dropped even if the test fails.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(extension: &str, contents: &[u8]) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "svg-text-rs-{}-{}.{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                extension
+            ));
+            fs::write(&path, contents).unwrap();
+
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn assert_font(font: Result<Font, String>) {
+        let font = font.unwrap();
+
+        assert_eq!(font.id(), Some("font"));
+        assert_eq!(font.text_width("é".to_string()), 600.0);
+    }
+
+    #[test]
+    fn it_loads_from_str() {
+        assert_font(Font::from_svg(FONT));
+        assert_font(FONT.parse::<Font>());
+    }
+
+    #[test]
+    fn it_detects_byte_order_marks() {
+        let utf8 = [&UTF8_BOM[..], FONT.as_bytes()].concat();
+        let utf16_le = FONT
+            .encode_utf16()
+            .fold(UTF16_LE_BOM.to_vec(), |mut bytes, unit| {
+                bytes.extend(unit.to_le_bytes());
+                bytes
+            });
+        let utf16_be = FONT
+            .encode_utf16()
+            .fold(UTF16_BE_BOM.to_vec(), |mut bytes, unit| {
+                bytes.extend(unit.to_be_bytes());
+                bytes
+            });
+
+        assert_font(Font::from_bytes(&utf8));
+        assert_font(Font::from_bytes(&utf16_le));
+        assert_font(Font::from_bytes(&utf16_be));
+        assert!(Font::from_bytes(&[0xff, 0xfe, 0x3c]).is_err());
+    }
+
+    #[test]
+    fn it_loads_gzip_compressed_fonts_from_readers_and_files() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(FONT.as_bytes()).unwrap();
+        let svgz = encoder.finish().unwrap();
+
+        let file = TempFile::new("svgz", &svgz);
+
+        assert_font(Font::from_reader(svgz.as_slice()));
+        assert_font(Font::from_file(&file.0));
+        assert!(Font::from_file(file.0.with_extension("missing")).is_err());
+    }
+
+    #[test]
+    fn it_loads_data_uris() {
+        let base64 = STANDARD.encode(FONT);
+
+        assert_font(Font::from_data_uri(&format!(
+            "data:image/svg+xml;base64,{}",
+            base64
+        )));
+        assert_font(Font::from_data_uri(&format!(
+            "url(\"data:font/svg;charset=utf-8;base64,{}\")",
+            base64
+        )));
+        assert_font(Font::from_data_uri(&format!(
+            "data:font/svg,{}",
+            FONT.replace('<', "%3C").replace('#', "%23")
+        )));
+        assert!(Font::from_data_uri("data:font/ttf;base64,AAAA").is_err());
+        assert!(Font::from_data_uri("https://example.com/font.svg").is_err());
+    }
+}
@@ -0,0 +1,584 @@
+use std::fmt::Write;
+
+use super::bounds::Bounds;
+use super::path::{
+    arc_to_cubics, arity, round, Path, Point, Segment, SegmentReader, POSSIBLE_PATH_CHARS,
+};
+
+/// Parses path data lazily, without allocating, yielding its segments as
+/// `Path::segments` does. Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct PathParser<'a> {
+    data: &'a str,
+    position: usize,
+    command: Option<char>,
+    first: bool,
+    reader: SegmentReader,
+    failed: bool,
+}
+
+/// Receives the segments of a path in absolute coordinates.
+pub trait PathSink {
+    fn move_to(&mut self, to: Point);
+
+    fn line_to(&mut self, to: Point);
+
+    fn quad_to(&mut self, control: Point, to: Point);
+
+    fn cubic_to(&mut self, control1: Point, control2: Point, to: Point);
+
+    /// Draws an arc starting at `from`, as cubic curves unless overridden.
+    fn arc_to(
+        &mut self,
+        from: Point,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) {
+        if radii.0 == 0.0 || radii.1 == 0.0 {
+            self.line_to(to);
+            return;
+        }
+
+        for (c1, c2, to) in arc_to_cubics(
+            from,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+            f64::INFINITY,
+        ) {
+            self.cubic_to(c1, c2, to);
+        }
+    }
+
+    fn close(&mut self);
+}
+
+impl<'a> PathParser<'a> {
+    pub fn new(data: &'a str) -> Self {
+        PathParser {
+            data,
+            position: 0,
+            command: None,
+            first: false,
+            reader: SegmentReader::default(),
+            failed: false,
+        }
+    }
+
+    /// Sends every segment to `sink`, stopping at the first error.
+    pub fn feed<S: PathSink>(self, sink: &mut S) -> Result<(), String> {
+        feed(self, sink)
+    }
+
+    fn parse_segment(&mut self) -> Option<Result<Segment, String>> {
+        self.skip_separators();
+
+        let byte = *self.data.as_bytes().get(self.position)?;
+
+        if byte.is_ascii_alphabetic() {
+            let command = byte as char;
+
+            if !POSSIBLE_PATH_CHARS.contains(&command) {
+                return Some(Err(format!(
+                    "Invalid path command '{}' at {}",
+                    command, self.position
+                )));
+            }
+
+            self.position += 1;
+            self.command = Some(command);
+            self.first = true;
+        } else if matches!(self.command, None | Some('Z') | Some('z')) {
+            return Some(Err(format!("Expected a path command at {}", self.position)));
+        }
+
+        let command = self.command?;
+        let mut args = [0.0; 7];
+
+        for (i, arg) in args.iter_mut().enumerate().take(arity(command)) {
+            self.skip_separators();
+
+            // Arc flags may be written without separators.
+            let value = if matches!(command, 'A' | 'a') && (i == 3 || i == 4) {
+                self.parse_flag()
+            } else {
+                self.parse_number()
+            };
+
+            *arg = match value {
+                Some(value) => value,
+                None => return Some(Err(format!("Expected a number at {}", self.position))),
+            };
+        }
+
+        let segment = self
+            .reader
+            .read(command, self.first, &args[..arity(command)]);
+        self.first = false;
+
+        Some(Ok(segment))
+    }
+
+    fn skip_separators(&mut self) {
+        let bytes = self.data.as_bytes();
+
+        while self.position < bytes.len()
+            && (bytes[self.position].is_ascii_whitespace() || bytes[self.position] == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn parse_flag(&mut self) -> Option<f64> {
+        match self.data.as_bytes().get(self.position) {
+            Some(b'0') => {
+                self.position += 1;
+                Some(0.0)
+            }
+            Some(b'1') => {
+                self.position += 1;
+                Some(1.0)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<f64> {
+        let bytes = self.data.as_bytes();
+        let start = self.position;
+        let mut end = start;
+        let digits = |mut end: usize| {
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+            end
+        };
+
+        if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+            end += 1;
+        }
+
+        let integer_end = digits(end);
+        let mut mantissa_end = integer_end;
+
+        if mantissa_end < bytes.len() && bytes[mantissa_end] == b'.' {
+            mantissa_end = digits(mantissa_end + 1);
+        }
+
+        // A lone sign or dot isn't a number.
+        if mantissa_end - end <= (mantissa_end > integer_end) as usize {
+            return None;
+        }
+
+        end = mantissa_end;
+
+        if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+            let mut exponent = end + 1;
+
+            if exponent < bytes.len() && (bytes[exponent] == b'+' || bytes[exponent] == b'-') {
+                exponent += 1;
+            }
+
+            if digits(exponent) > exponent {
+                end = digits(exponent);
+            }
+        }
+
+        self.position = end;
+        self.data[start..end].parse().ok()
+    }
+}
+
+impl<'a> Iterator for PathParser<'a> {
+    type Item = Result<Segment, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let segment = self.parse_segment();
+        self.failed = matches!(segment, Some(Err(_)));
+
+        segment
+    }
+}
+
+impl Path {
+    /// Sends every segment of the path to `sink`.
+    pub fn feed<S: PathSink>(&self, sink: &mut S) {
+        feed(self.segments().map(Ok), sink).unwrap_or_default()
+    }
+}
+
+fn feed<I, S>(segments: I, sink: &mut S) -> Result<(), String>
+where
+    I: Iterator<Item = Result<Segment, String>>,
+    S: PathSink,
+{
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+
+    for segment in segments {
+        match segment? {
+            Segment::MoveTo { relative, to } => {
+                current = absolute(current, relative, to);
+                start = current;
+                sink.move_to(current);
+            }
+            Segment::LineTo { relative, to } => {
+                let to = absolute(current, relative, to);
+                sink.line_to(to);
+                current = to;
+            }
+            Segment::QuadTo {
+                relative,
+                control,
+                to,
+            } => {
+                let to = absolute(current, relative, to);
+                sink.quad_to(absolute(current, relative, control), to);
+                current = to;
+            }
+            Segment::CubicTo {
+                relative,
+                control1,
+                control2,
+                to,
+            } => {
+                let to = absolute(current, relative, to);
+                sink.cubic_to(
+                    absolute(current, relative, control1),
+                    absolute(current, relative, control2),
+                    to,
+                );
+                current = to;
+            }
+            Segment::ArcTo {
+                relative,
+                radii,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                let to = absolute(current, relative, to);
+                sink.arc_to(current, radii, x_axis_rotation, large_arc, sweep, to);
+                current = to;
+            }
+            Segment::Close => {
+                sink.close();
+                current = start;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn absolute(current: Point, relative: bool, (x, y): Point) -> Point {
+    if relative {
+        (current.0 + x, current.1 + y)
+    } else {
+        (x, y)
+    }
+}
+
+/// Bounds of the points a path goes through, as `Path::bounds`.
+#[derive(Debug, Default)]
+pub struct BoundsSink {
+    bounds: Option<Bounds>,
+}
+
+impl BoundsSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bounds, `None` when no segment was received.
+    pub fn into_bounds(self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    fn extend(&mut self, (x, y): Point) {
+        self.bounds = Some(match self.bounds.take() {
+            Some(bounds) => bounds.extends(x, y),
+            None => Bounds::new().move_last_point(x, y, true),
+        });
+    }
+}
+
+impl PathSink for BoundsSink {
+    fn move_to(&mut self, to: Point) {
+        self.extend(to);
+    }
+
+    fn line_to(&mut self, to: Point) {
+        self.extend(to);
+    }
+
+    fn quad_to(&mut self, _: Point, to: Point) {
+        self.extend(to);
+    }
+
+    fn cubic_to(&mut self, _: Point, _: Point, to: Point) {
+        self.extend(to);
+    }
+
+    fn arc_to(&mut self, _: Point, _: Point, _: f64, _: bool, _: bool, to: Point) {
+        self.extend(to);
+    }
+
+    fn close(&mut self) {}
+}
+
+/// Writes path data with absolute commands, numbers being rounded to
+/// `precision` decimals when given.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeSink {
+    data: String,
+    precision: Option<usize>,
+}
+
+impl SerializeSink {
+    pub fn new(precision: Option<usize>) -> Self {
+        SerializeSink {
+            data: String::new(),
+            precision,
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        self.data
+    }
+
+    fn write(&mut self, command: char, args: &[f64]) {
+        self.data.push(command);
+
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                self.data.push(' ');
+            }
+
+            match self.precision {
+                Some(precision) => write!(self.data, "{}", round(*arg, precision)).unwrap(),
+                None => write!(self.data, "{}", arg).unwrap(),
+            }
+        }
+    }
+}
+
+impl PathSink for SerializeSink {
+    fn move_to(&mut self, to: Point) {
+        self.write('M', &[to.0, to.1]);
+    }
+
+    fn line_to(&mut self, to: Point) {
+        self.write('L', &[to.0, to.1]);
+    }
+
+    fn quad_to(&mut self, control: Point, to: Point) {
+        self.write('Q', &[control.0, control.1, to.0, to.1]);
+    }
+
+    fn cubic_to(&mut self, control1: Point, control2: Point, to: Point) {
+        self.write(
+            'C',
+            &[control1.0, control1.1, control2.0, control2.1, to.0, to.1],
+        );
+    }
+
+    fn arc_to(
+        &mut self,
+        _: Point,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) {
+        self.write(
+            'A',
+            &[
+                radii.0,
+                radii.1,
+                x_axis_rotation,
+                large_arc as u8 as f64,
+                sweep as u8 as f64,
+                to.0,
+                to.1,
+            ],
+        );
+    }
+
+    fn close(&mut self) {
+        self.write('Z', &[]);
+    }
+}
+
+/// Applies an affine transformation, the SVG matrix `[a, b, c, d, e, f]`,
+/// before passing segments on to another sink. Arcs become cubic curves.
+#[derive(Debug, Clone)]
+pub struct TransformSink<S: PathSink> {
+    matrix: [f64; 6],
+    sink: S,
+}
+
+impl<S: PathSink> TransformSink<S> {
+    pub fn new(matrix: [f64; 6], sink: S) -> Self {
+        TransformSink { matrix, sink }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    fn apply(&self, (x, y): Point) -> Point {
+        let [a, b, c, d, e, f] = self.matrix;
+
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+}
+
+impl<S: PathSink> PathSink for TransformSink<S> {
+    fn move_to(&mut self, to: Point) {
+        let to = self.apply(to);
+        self.sink.move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point) {
+        let to = self.apply(to);
+        self.sink.line_to(to);
+    }
+
+    fn quad_to(&mut self, control: Point, to: Point) {
+        let (control, to) = (self.apply(control), self.apply(to));
+        self.sink.quad_to(control, to);
+    }
+
+    fn cubic_to(&mut self, control1: Point, control2: Point, to: Point) {
+        let (control1, control2, to) = (self.apply(control1), self.apply(control2), self.apply(to));
+        self.sink.cubic_to(control1, control2, to);
+    }
+
+    fn close(&mut self) {
+        self.sink.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_parses_compact_path_data() {
+        let segments = PathParser::new("M10,20l5-5h.5e1 a5 5 0 1010 0z")
+            .collect::<Result<Vec<Segment>, String>>()
+            .unwrap();
+
+        assert_eq!(
+            segments,
+            vec![
+                Segment::MoveTo {
+                    relative: false,
+                    to: (10.0, 20.0)
+                },
+                Segment::LineTo {
+                    relative: true,
+                    to: (5.0, -5.0)
+                },
+                Segment::LineTo {
+                    relative: true,
+                    to: (5.0, 0.0)
+                },
+                Segment::ArcTo {
+                    relative: true,
+                    radii: (5.0, 5.0),
+                    x_axis_rotation: 0.0,
+                    large_arc: true,
+                    sweep: false,
+                    to: (10.0, 0.0)
+                },
+                Segment::Close,
+            ]
+        );
+
+        let d = "M 10 20 L 30 40 50 60 S 1 2 3 4 T 5 6 z m 1 1 2 2";
+
+        assert!(PathParser::new(d)
+            .map(Result::unwrap)
+            .eq(Path::new(d.to_string()).unwrap().segments()));
+    }
+
+    #[test]
+    fn it_stops_at_the_first_error() {
+        let mut parser = PathParser::new("M10 20 X5");
+
+        assert!(parser.next().unwrap().is_ok());
+        assert_eq!(
+            parser.next(),
+            Some(Err("Invalid path command 'X' at 7".to_string()))
+        );
+        assert_eq!(parser.next(), None);
+
+        assert_eq!(
+            PathParser::new("10 20").next(),
+            Some(Err("Expected a path command at 0".to_string()))
+        );
+        assert_eq!(
+            PathParser::new("M10 -").feed(&mut BoundsSink::new()),
+            Err("Expected a number at 4".to_string())
+        );
+    }
+
+    #[test]
+    fn it_feeds_sinks() {
+        let mut bounds = BoundsSink::new();
+        PathParser::new("M10 20 l5 -30 H-5 v40")
+            .feed(&mut bounds)
+            .unwrap();
+        let bounds = bounds.into_bounds().unwrap();
+
+        assert_eq!(
+            (bounds.x1(), bounds.y1(), bounds.x2(), bounds.y2()),
+            (-5.0, -10.0, 15.0, 30.0)
+        );
+
+        let mut serializer = SerializeSink::new(Some(1));
+        PathParser::new("m1 2 q1 1 2 0 a5 5 0 0 1 10 0 z")
+            .feed(&mut serializer)
+            .unwrap();
+
+        assert_eq!(serializer.into_string(), "M1 2Q2 3 3 2A5 5 0 0 1 13 2Z");
+
+        let mut transform =
+            TransformSink::new([2.0, 0.0, 0.0, -2.0, 100.0, 0.0], SerializeSink::new(None));
+        Path::new("M0 0 H10 V10 Z".to_string())
+            .unwrap()
+            .feed(&mut transform);
+
+        assert_eq!(
+            transform.into_inner().into_string(),
+            "M100 0L120 0L120 -20Z"
+        );
+    }
+
+    #[test]
+    fn it_parses_glyphs_as_paths_do() {
+        let font = crate::svg::font::Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        for glyph in font.glyphs() {
+            let path = Path::new(glyph.path.clone()).unwrap();
+
+            assert!(PathParser::new(&glyph.path)
+                .map(Result::unwrap)
+                .eq(path.segments()));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use super::path::{Path, Point, Segment};
+use super::stream::PathSink;
+
+impl Path {
+    /// Every subpath as a path of its own, with absolute coordinates.
+    pub fn subpaths(&self) -> impl Iterator<Item = Path> {
+        contours(self)
+            .into_iter()
+            .map(|contour| Path::from_segments(&contour))
+    }
+
+    /// The same shape drawn backwards, the last subpath first and each one
+    /// from its end to its start. Curves and arcs are kept as they are.
+    pub fn reverse(&self) -> Path {
+        let segments = contours(self)
+            .iter()
+            .rev()
+            .flat_map(|contour| reverse_contour(contour))
+            .collect::<Vec<Segment>>();
+
+        Path::from_segments(&segments)
+    }
+}
+
+/// The segments of every subpath with absolute coordinates, each subpath
+/// starting with a moveto.
+fn contours(path: &Path) -> Vec<Vec<Segment>> {
+    let mut sink = ContourSink {
+        contours: Vec::new(),
+        start: (0.0, 0.0),
+        closed: true,
+    };
+    path.feed(&mut sink);
+
+    sink.contours
+}
+
+fn reverse_contour(contour: &[Segment]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut start = (0.0, 0.0);
+    let mut current = (0.0, 0.0);
+    let mut closed = false;
+
+    for segment in contour {
+        match *segment {
+            Segment::MoveTo { to, .. } => {
+                start = to;
+                current = to;
+            }
+            Segment::LineTo { to, .. }
+            | Segment::QuadTo { to, .. }
+            | Segment::CubicTo { to, .. }
+            | Segment::ArcTo { to, .. } => {
+                segments.push((current, *segment));
+                current = to;
+            }
+            Segment::Close => closed = true,
+        }
+    }
+
+    // The closing line becomes the first segment drawn.
+    if closed && current != start {
+        segments.push((
+            current,
+            Segment::LineTo {
+                relative: false,
+                to: start,
+            },
+        ));
+        current = start;
+    }
+
+    let mut reversed = vec![Segment::MoveTo {
+        relative: false,
+        to: current,
+    }];
+
+    for (from, segment) in segments.into_iter().rev() {
+        reversed.push(match segment {
+            Segment::QuadTo { control, .. } => Segment::QuadTo {
+                relative: false,
+                control,
+                to: from,
+            },
+            Segment::CubicTo {
+                control1, control2, ..
+            } => Segment::CubicTo {
+                relative: false,
+                control1: control2,
+                control2: control1,
+                to: from,
+            },
+            Segment::ArcTo {
+                radii,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                ..
+            } => Segment::ArcTo {
+                relative: false,
+                radii,
+                x_axis_rotation,
+                large_arc,
+                sweep: !sweep,
+                to: from,
+            },
+            _ => Segment::LineTo {
+                relative: false,
+                to: from,
+            },
+        });
+    }
+
+    if closed {
+        reversed.push(Segment::Close);
+    }
+
+    reversed
+}
+
+/// Collects segments with absolute coordinates, subpath by subpath.
+struct ContourSink {
+    contours: Vec<Vec<Segment>>,
+    start: Point,
+    closed: bool,
+}
+
+impl ContourSink {
+    fn push(&mut self, segment: Segment) {
+        // Drawing after a closepath starts again from its start.
+        if self.closed {
+            self.move_to(self.start);
+        }
+
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push(segment);
+        }
+    }
+}
+
+impl PathSink for ContourSink {
+    fn move_to(&mut self, to: Point) {
+        self.contours.push(vec![Segment::MoveTo {
+            relative: false,
+            to,
+        }]);
+        self.start = to;
+        self.closed = false;
+    }
+
+    fn line_to(&mut self, to: Point) {
+        self.push(Segment::LineTo {
+            relative: false,
+            to,
+        });
+    }
+
+    fn quad_to(&mut self, control: Point, to: Point) {
+        self.push(Segment::QuadTo {
+            relative: false,
+            control,
+            to,
+        });
+    }
+
+    fn cubic_to(&mut self, control1: Point, control2: Point, to: Point) {
+        self.push(Segment::CubicTo {
+            relative: false,
+            control1,
+            control2,
+            to,
+        });
+    }
+
+    fn arc_to(
+        &mut self,
+        _: Point,
+        radii: Point,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) {
+        self.push(Segment::ArcTo {
+            relative: false,
+            radii,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            to,
+        });
+    }
+
+    fn close(&mut self) {
+        if !self.closed {
+            self.push(Segment::Close);
+            self.closed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::svg::testing::{path, ARIAL_SVG_FONT};
+
+    #[test]
+    fn it_reverses_a_path() {
+        let path = path("M0 0 L10 0 Q15 5 10 10 C5 15 0 15 0 10 a5 5 0 0 1 -5 -5 Z M20 20 L30 30");
+
+        assert_eq!(
+            path.reverse().to_string(),
+            "M30 30L20 20M0 0L-5 5A5 5 0 0 0 0 10C0 15 5 15 10 10Q15 5 10 0L0 0Z"
+        );
+        assert_eq!(path.reverse().signed_area(), -path.signed_area());
+        assert_eq!(
+            path.reverse().reverse().to_string(),
+            "M0 0L10 0Q15 5 10 10C5 15 0 15 0 10A5 5 0 0 1 -5 5L0 0ZM20 20L30 30"
+        );
+    }
+
+    #[test]
+    fn it_splits_subpaths() {
+        let subpaths = path("M0 0 H10 V10 Z Z l5 5 m10 0 h1 M7 7")
+            .subpaths()
+            .map(|subpath| subpath.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            subpaths,
+            vec!["M0 0L10 0L10 10Z", "M0 0L5 5", "M15 5L16 5", "M7 7"]
+        );
+    }
+
+    #[test]
+    fn it_bounds_every_contour_of_a_glyph() {
+        let font = crate::svg::font::Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let bounds = |unicode: char| {
+            let glyph = font
+                .glyphs()
+                .iter()
+                .find(|g| g.unicode == unicode as u32)
+                .unwrap();
+
+            path(&glyph.path)
+                .subpaths()
+                .map(|subpath| {
+                    let bounds = subpath.bounds().unwrap();
+                    (bounds.x1(), bounds.y1(), bounds.x2(), bounds.y2())
+                })
+                .collect::<Vec<(f64, f64, f64, f64)>>()
+        };
+
+        assert_eq!(
+            bounds('i'),
+            vec![(139.0, 0.0, 328.0, 1061.0), (139.0, 1262.0, 328.0, 1466.0)]
+        );
+
+        let eacute = bounds('é');
+
+        assert_eq!(eacute.len(), 3);
+        assert_eq!(eacute[2], (457.0, 1210.0, 826.0, 1491.0));
+    }
+}
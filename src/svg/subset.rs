@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+
+use unicode_normalization::UnicodeNormalization;
+
+use super::arabic::{presentation_form, ArabicForm};
+use super::bidi::mirror;
+use super::export::ExportOptions;
+use super::font::{Font, Glyph};
+
+const FORMS: [ArabicForm; 4] = [
+    ArabicForm::Isolated,
+    ArabicForm::Initial,
+    ArabicForm::Medial,
+    ArabicForm::Final,
+];
+
+/// How much smaller a subset is than the font it was made from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubsetReport {
+    pub glyphs_before: usize,
+    pub glyphs_after: usize,
+    /// Sizes of the fonts exported as SVG documents.
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+impl SubsetReport {
+    /// The share of the exported size saved, between 0 and 1.
+    pub fn reduction(&self) -> f64 {
+        if self.bytes_before == 0 {
+            return 0.0;
+        }
+
+        1.0 - self.bytes_after as f64 / self.bytes_before as f64
+    }
+}
+
+impl Font {
+    /// Keeps the glyphs needed to render text made of `characters`: their
+    /// glyphs, decompositions, mirrored brackets, contextual forms and
+    /// ligatures, with the kerning between kept glyphs and the missing glyph.
+    ///
+    /// See `subset_text` to subset a font for a given text.
+    pub fn subset<I: IntoIterator<Item = char>>(&self, characters: I) -> Font {
+        let mut wanted = HashSet::new();
+
+        for character in characters {
+            wanted.insert(character);
+            wanted.insert(mirror(character));
+            wanted.extend(std::iter::once(character).nfd());
+
+            for form in FORMS {
+                wanted.extend(presentation_form(character, form));
+            }
+        }
+
+        // Precomposed characters are kept when everything they decompose to is.
+        let kept = self
+            .glyphs()
+            .iter()
+            .filter(|glyph| {
+                glyph.characters.iter().all(|character| {
+                    wanted.contains(character)
+                        || std::iter::once(*character)
+                            .nfd()
+                            .all(|c| wanted.contains(&c))
+                })
+            })
+            .collect::<Vec<&Glyph>>();
+
+        let ids = kept
+            .iter()
+            .enumerate()
+            .map(|(id, glyph)| (glyph.id, id))
+            .collect::<HashMap<usize, usize>>();
+
+        let kerning = self
+            .kerning
+            .iter()
+            .filter_map(
+                |((first, second), k)| match (ids.get(first), ids.get(second)) {
+                    (Some(first), Some(second)) => Some(((*first, *second), *k)),
+                    _ => None,
+                },
+            )
+            .collect::<HashMap<(usize, usize), f64>>();
+
+        let glyphs = kept
+            .into_iter()
+            .map(|glyph| Glyph {
+                id: ids[&glyph.id],
+                ..glyph.clone()
+            })
+            .collect::<Vec<Glyph>>();
+
+        Font {
+            id: self.id.clone(),
+            horizontal_advance_x: self.horizontal_advance_x,
+            units_per_em: self.units_per_em,
+            ascent: self.ascent,
+            descent: self.descent,
+            family: self.family.clone(),
+            weight: self.weight,
+            style: self.style,
+            stretch: self.stretch,
+            font_face: self.font_face.clone(),
+            missing_glyph: self.missing_glyph.as_ref().map(|glyph| Glyph {
+                id: glyphs.len(),
+                ..glyph.clone()
+            }),
+            glyphs,
+            kerning,
+        }
+    }
+
+    /// Keeps the glyphs needed to render `text`, see `subset`.
+    pub fn subset_text(&self, text: &str) -> Font {
+        self.subset(text.chars())
+    }
+
+    /// Compares the font with a `subset` made from it.
+    pub fn subset_report(&self, subset: &Font) -> SubsetReport {
+        let options = ExportOptions::default();
+
+        SubsetReport {
+            glyphs_before: self.glyphs().len(),
+            glyphs_after: subset.glyphs().len(),
+            bytes_before: self.to_svg(&options).len(),
+            bytes_after: subset.to_svg(&options).len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_keeps_the_glyphs_used_by_a_text() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let subset = font.subset_text("Hello World");
+
+        assert!(subset.has_glyph('H'));
+        assert!(subset.has_glyph(' '));
+        assert!(!subset.has_glyph('a'));
+        assert_eq!(subset.glyphs().len(), 8);
+        assert_eq!(subset.missing_glyph_advance(), Some(600.0));
+        assert_eq!(
+            subset.text_width("Hello World".to_string()),
+            font.text_width("Hello World".to_string())
+        );
+
+        let report = font.subset_report(&subset);
+
+        assert_eq!(report.glyphs_after, 8);
+        assert!(report.glyphs_before > 100);
+        assert!(report.bytes_after < report.bytes_before);
+        assert!(report.reduction() > 0.9);
+    }
+
+    #[test]
+    fn it_keeps_ligatures_forms_and_kerning_between_kept_glyphs() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph glyph-name="fi" unicode="fi" horiz-adv-x="450" />
+                    <glyph glyph-name="f" unicode="f" horiz-adv-x="300" />
+                    <glyph glyph-name="i" unicode="i" horiz-adv-x="200" />
+                    <glyph glyph-name="o" unicode="o" horiz-adv-x="500" />
+                    <glyph glyph-name="eacute" unicode="&#xe9;" horiz-adv-x="500" />
+                    <glyph glyph-name="lam.init" unicode="&#xfedf;" horiz-adv-x="500" />
+                    <hkern g1="f" g2="i" k="20" />
+                    <hkern g1="f" g2="o" k="30" />
+                </font>
+            </svg>"#;
+
+        let font = Font::new(data.to_string()).unwrap();
+        let subset = font.subset("if\u{644}".chars().collect::<HashSet<char>>());
+
+        assert!(subset.has_glyph('f'));
+        assert!(subset.has_glyph('\u{fedf}'));
+        assert!(!subset.has_glyph('o'));
+        assert!(!subset.has_glyph('é'));
+        assert_eq!(subset.text_width("fi".to_string()), 450.0);
+        assert_eq!(subset.kerning('f', 'i'), -20.0);
+        assert_eq!(subset.kerning.len(), 1);
+        assert!(Font::new(subset.to_svg(&ExportOptions::default())).is_ok());
+    }
+}
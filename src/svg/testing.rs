@@ -0,0 +1,9 @@
+//! Fixtures and helpers shared by the tests of the `svg` modules.
+
+use super::path::Path;
+
+pub(crate) const ARIAL_SVG_FONT: &str = include_str!("../../fixtures/arial.svg");
+
+pub(crate) fn path(d: &str) -> Path {
+    Path::new(d.to_string()).unwrap()
+}
@@ -0,0 +1,949 @@
+use std::collections::HashMap;
+
+use super::font::{Font, Glyph};
+use super::matching::FontStyle;
+use super::opentype::WIDTHS;
+use super::path::{Element, Path, Point};
+
+#[derive(Debug, Clone)]
+pub struct TrueTypeOptions {
+    /// Maximum distance, in font units, between a cubic curve and the
+    /// quadratic curves replacing it.
+    pub tolerance: f64,
+    /// Writes the kerning pairs to a `kern` table.
+    pub kerning: bool,
+}
+
+impl Default for TrueTypeOptions {
+    fn default() -> Self {
+        TrueTypeOptions {
+            tolerance: 1.0,
+            kerning: true,
+        }
+    }
+}
+
+/// A point of a TrueType contour, either on the curve or a control point.
+type ContourPoint = (i32, i32, bool);
+
+/// A glyph outline encoded for the `glyf` table.
+struct Outline {
+    data: Vec<u8>,
+    bounds: (i16, i16, i16, i16),
+    points: usize,
+    contours: usize,
+}
+
+impl Font {
+    /// Compiles the font to a TrueType font which `Font::from_opentype` loads
+    /// back.
+    ///
+    /// Every character with a glyph is mapped, in its isolated form and for
+    /// any language. Ligatures and other contextual glyphs are left out, as
+    /// there is no `GSUB` table to reach them. Cubic curves are approximated
+    /// with quadratic curves within `options.tolerance`.
+    pub fn to_truetype(&self, options: &TrueTypeOptions) -> Result<Vec<u8>, String> {
+        if !(16.0..=16384.0).contains(&self.units_per_em.round()) {
+            return Err(format!(
+                "TrueType units per em must be between 16 and 16384, got {}",
+                self.units_per_em
+            ));
+        }
+
+        let mut characters = self
+            .glyphs
+            .iter()
+            .filter(|glyph| glyph.characters.len() == 1)
+            .map(|glyph| glyph.characters[0])
+            .collect::<Vec<char>>();
+        characters.sort();
+        characters.dedup();
+
+        let notdef = Glyph {
+            id: usize::MAX,
+            name: Some(".notdef".to_string()),
+            unicode: 0,
+            characters: Vec::new(),
+            arabic_form: None,
+            lang: Vec::new(),
+            orientation: None,
+            horizontal_advance_x: self.horizontal_advance_x,
+            path: String::new(),
+        };
+
+        let mut glyphs = vec![self.missing_glyph.as_ref().unwrap_or(&notdef)];
+        let mut glyph_ids = HashMap::new();
+        let mut mapping = Vec::new();
+
+        for character in characters {
+            if let Some(glyph) = self.glyph_for(character) {
+                let glyph_id = *glyph_ids.entry(glyph.id).or_insert_with(|| {
+                    glyphs.push(glyph);
+                    glyphs.len() - 1
+                });
+
+                mapping.push((character as u32, glyph_id as u16));
+            }
+        }
+
+        if glyphs.len() > u16::MAX as usize {
+            return Err("Too many glyphs for a TrueType font".to_string());
+        }
+
+        let outlines = glyphs
+            .iter()
+            .map(|glyph| outline(&glyph.path, options.tolerance))
+            .collect::<Result<Vec<Outline>, String>>()?;
+
+        let advances = glyphs
+            .iter()
+            .map(|glyph| {
+                glyph
+                    .horizontal_advance_x
+                    .round()
+                    .clamp(0.0, u16::MAX as f64) as u16
+            })
+            .collect::<Vec<u16>>();
+
+        let mut kerning = Vec::new();
+
+        if options.kerning {
+            for ((first, second), k) in &self.kerning {
+                if let (Some(first), Some(second)) = (glyph_ids.get(first), glyph_ids.get(second)) {
+                    let value = (-k).round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+
+                    if value != 0 {
+                        kerning.push((*first as u16, *second as u16, value));
+                    }
+                }
+            }
+
+            kerning.sort();
+        }
+
+        let mut tables = vec![
+            (*b"OS/2", self.os2_table(&outlines, &advances, &mapping)),
+            (*b"cmap", cmap_table(&mapping)),
+            (*b"head", self.head_table(&outlines)),
+            (*b"hhea", self.hhea_table(&outlines, &advances)),
+            (*b"hmtx", hmtx_table(&outlines, &advances)),
+            (*b"maxp", maxp_table(&outlines)),
+            (*b"name", self.name_table()),
+            (*b"post", self.post_table(&glyphs)),
+        ];
+
+        let mut glyf = Vec::new();
+        let mut loca = Buffer::new();
+
+        for outline in &outlines {
+            loca.u32(glyf.len() as u32);
+            glyf.extend(&outline.data);
+        }
+
+        loca.u32(glyf.len() as u32);
+
+        tables.push((*b"glyf", glyf));
+        tables.push((*b"loca", loca.0));
+
+        if !kerning.is_empty() {
+            tables.push((*b"kern", kern_table(&kerning)?));
+        }
+
+        Ok(sfnt(tables))
+    }
+
+    /// The family name, falling back to the font id.
+    fn family_name(&self) -> String {
+        match (&self.family, &self.id) {
+            (Some(family), _) => family.clone(),
+            (None, Some(id)) => id.clone(),
+            (None, None) => "Untitled".to_string(),
+        }
+    }
+
+    fn subfamily_name(&self) -> &'static str {
+        let bold = self.weight >= 700.0;
+
+        match (bold, self.style) {
+            (false, FontStyle::Normal) => "Regular",
+            (true, FontStyle::Normal) => "Bold",
+            (false, _) => "Italic",
+            (true, _) => "Bold Italic",
+        }
+    }
+
+    /// A number of a `font-face` attribute, such as `x-height`.
+    fn font_face_number(&self, name: &str) -> Option<f64> {
+        self.font_face
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .and_then(|(_, value)| value.trim().parse::<f64>().ok())
+    }
+
+    fn head_table(&self, outlines: &[Outline]) -> Vec<u8> {
+        let (x_min, y_min, x_max, y_max) = font_bounds(outlines);
+        let mut mac_style = 0;
+
+        if self.weight >= 700.0 {
+            mac_style |= 0x01;
+        }
+
+        if self.style != FontStyle::Normal {
+            mac_style |= 0x02;
+        }
+
+        let mut head = Buffer::new();
+        head.u32(0x00010000).u32(0x00010000).u32(0).u32(0x5f0f3cf5);
+        head.u16(0x0003).u16(self.units_per_em.round() as u16);
+        head.u32(0).u32(0).u32(0).u32(0);
+        head.i16(x_min).i16(y_min).i16(x_max).i16(y_max);
+        head.u16(mac_style).u16(8).i16(2).i16(1).i16(0);
+
+        head.0
+    }
+
+    fn hhea_table(&self, outlines: &[Outline], advances: &[u16]) -> Vec<u8> {
+        let drawn = outlines
+            .iter()
+            .zip(advances)
+            .filter(|(outline, _)| outline.contours > 0);
+
+        let min_left = drawn.clone().map(|(o, _)| o.bounds.0).min().unwrap_or(0);
+        let min_right = drawn
+            .clone()
+            .map(|(o, advance)| *advance as i32 - o.bounds.2 as i32)
+            .min()
+            .unwrap_or(0);
+        let max_extent = drawn.map(|(o, _)| o.bounds.2).max().unwrap_or(0);
+
+        let mut hhea = Buffer::new();
+        hhea.u32(0x00010000);
+        hhea.i16(clamp(self.ascent)).i16(clamp(self.descent)).i16(0);
+        hhea.u16(advances.iter().copied().max().unwrap_or(0));
+        hhea.i16(min_left)
+            .i16(clamp(min_right as f64))
+            .i16(max_extent);
+        hhea.i16(1).i16(0).i16(0);
+        hhea.i16(0).i16(0).i16(0).i16(0).i16(0);
+        hhea.u16(advances.len() as u16);
+
+        hhea.0
+    }
+
+    fn name_table(&self) -> Vec<u8> {
+        let family = self.family_name();
+        let subfamily = self.subfamily_name();
+        let postscript = format!("{}-{}", family, subfamily)
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .take(63)
+            .collect::<String>();
+
+        let names = [
+            (1, family.clone()),
+            (2, subfamily.to_string()),
+            (3, postscript.clone()),
+            (4, format!("{} {}", family, subfamily)),
+            (6, postscript),
+        ];
+
+        let strings = names
+            .iter()
+            .map(|(_, name)| {
+                name.encode_utf16()
+                    .flat_map(|unit| unit.to_be_bytes())
+                    .collect::<Vec<u8>>()
+            })
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut name = Buffer::new();
+        name.u16(0).u16(names.len() as u16);
+        name.u16(6 + 12 * names.len() as u16);
+
+        let mut offset = 0;
+
+        for ((id, _), string) in names.iter().zip(&strings) {
+            name.u16(3).u16(1).u16(0x409).u16(*id);
+            name.u16(string.len() as u16).u16(offset);
+            offset += string.len() as u16;
+        }
+
+        for string in strings {
+            name.bytes(&string);
+        }
+
+        name.0
+    }
+
+    fn os2_table(&self, outlines: &[Outline], advances: &[u16], mapping: &[(u32, u16)]) -> Vec<u8> {
+        let em = self.units_per_em;
+        let (_, y_min, _, y_max) = font_bounds(outlines);
+
+        let drawn = advances
+            .iter()
+            .filter(|advance| **advance > 0)
+            .map(|advance| *advance as f64)
+            .collect::<Vec<f64>>();
+        let average = drawn.iter().sum::<f64>() / drawn.len().max(1) as f64;
+
+        let width_class = WIDTHS
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (*a - self.stretch)
+                    .abs()
+                    .total_cmp(&(*b - self.stretch).abs())
+            })
+            .map(|(i, _)| i as u16 + 1)
+            .unwrap_or(5);
+
+        // Typographic metrics are the ones to use.
+        let mut selection = 0x80;
+
+        match self.style {
+            FontStyle::Normal if self.weight < 700.0 => selection |= 0x40,
+            FontStyle::Normal => {}
+            FontStyle::Italic => selection |= 0x01,
+            FontStyle::Oblique => selection |= 0x201,
+        }
+
+        if self.weight >= 700.0 {
+            selection |= 0x20;
+        }
+
+        let panose = self
+            .font_face
+            .iter()
+            .find(|(attribute, _)| attribute == "panose-1")
+            .map(|(_, value)| {
+                value
+                    .split_whitespace()
+                    .filter_map(|digit| digit.parse::<u8>().ok())
+                    .collect::<Vec<u8>>()
+            })
+            .filter(|panose| panose.len() == 10)
+            .unwrap_or_else(|| vec![0; 10]);
+
+        let first = mapping.first().map_or(0, |(c, _)| (*c).min(0xffff));
+        let last = mapping.last().map_or(0, |(c, _)| (*c).min(0xffff));
+
+        let mut os2 = Buffer::new();
+        os2.u16(4).i16(clamp(average));
+        os2.u16(self.weight.round().clamp(1.0, 1000.0) as u16);
+        os2.u16(width_class).u16(0);
+
+        // Subscript, superscript and strikeout metrics, in em.
+        for size in [0.65, 0.6, 0.0, 0.075, 0.65, 0.6, 0.0, 0.35, 0.05, 0.25] {
+            os2.i16(clamp(size * em));
+        }
+
+        os2.i16(0).bytes(&panose);
+        os2.u32(0).u32(0).u32(0).u32(0);
+        os2.bytes(b"NONE").u16(selection);
+        os2.u16(first as u16).u16(last as u16);
+        os2.i16(clamp(self.ascent)).i16(clamp(self.descent)).i16(0);
+        os2.u16(clamp(self.ascent.max(y_max as f64)).max(0) as u16);
+        os2.u16(clamp(-self.descent.min(y_min as f64)).max(0) as u16);
+        os2.u32(1).u32(0);
+        os2.i16(clamp(self.font_face_number("x-height").unwrap_or(0.0)));
+        os2.i16(clamp(self.font_face_number("cap-height").unwrap_or(0.0)));
+        os2.u16(0)
+            .u16(32)
+            .u16(if self.kerning.is_empty() { 1 } else { 2 });
+
+        os2.0
+    }
+
+    fn post_table(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let mut post = Buffer::new();
+        post.u32(0x00020000).u32(0);
+        post.i16(clamp(
+            self.font_face_number("underline-position").unwrap_or(0.0),
+        ));
+        post.i16(clamp(
+            self.font_face_number("underline-thickness").unwrap_or(0.0),
+        ));
+        post.u32(0).u32(0).u32(0).u32(0).u32(0);
+        post.u16(glyphs.len() as u16);
+
+        let mut names: Vec<String> = Vec::new();
+
+        for glyph in &glyphs[1..] {
+            let name = match &glyph.name {
+                Some(name)
+                    if name.len() < 64
+                        && name != ".notdef"
+                        && name.chars().all(|c| c.is_ascii_graphic())
+                        && !names.contains(name) =>
+                {
+                    name.clone()
+                }
+                _ if glyph.unicode > 0xffff => format!("u{:X}", glyph.unicode),
+                _ => format!("uni{:04X}", glyph.unicode),
+            };
+
+            names.push(name);
+        }
+
+        // The first glyph uses the standard `.notdef` name.
+        post.u16(0);
+
+        for i in 0..names.len() {
+            post.u16(258 + i as u16);
+        }
+
+        for name in names {
+            post.u8(name.len() as u8).bytes(name.as_bytes());
+        }
+
+        post.0
+    }
+}
+
+/// Converts SVG path data to TrueType contours and encodes them.
+fn outline(path: &str, tolerance: f64) -> Result<Outline, String> {
+    let path = match Path::new(path.to_string()) {
+        Ok(path) => path,
+        Err(_) => return Err(format!("Invalid glyph path: {}", path)),
+    };
+
+    let mut contours: Vec<Vec<ContourPoint>> = Vec::new();
+    let mut contour: Vec<(f64, f64, bool)> = Vec::new();
+    let mut current = (0.0, 0.0);
+
+    for element in path.elements() {
+        match element {
+            Element::MoveTo(point) => {
+                contours.extend(finish_contour(&contour)?);
+                contour = vec![(point.0, point.1, true)];
+                current = point;
+            }
+            Element::LineTo(point) => {
+                contour.push((point.0, point.1, true));
+                current = point;
+            }
+            Element::QuadTo(control, point) => {
+                contour.push((control.0, control.1, false));
+                contour.push((point.0, point.1, true));
+                current = point;
+            }
+            Element::CubicTo(c1, c2, point) => {
+                let mut quadratics = Vec::new();
+                cubic_to_quadratics(
+                    current,
+                    c1,
+                    c2,
+                    point,
+                    tolerance.max(0.01),
+                    0,
+                    &mut quadratics,
+                );
+
+                for (control, end) in quadratics {
+                    contour.push((control.0, control.1, false));
+                    contour.push((end.0, end.1, true));
+                }
+
+                current = point;
+            }
+            Element::Close => {
+                contours.extend(finish_contour(&contour)?);
+                contour.clear();
+            }
+        }
+    }
+
+    contours.extend(finish_contour(&contour)?);
+
+    let points = contours.iter().flatten().collect::<Vec<&ContourPoint>>();
+
+    if points.is_empty() {
+        return Ok(Outline {
+            data: Vec::new(),
+            bounds: (0, 0, 0, 0),
+            points: 0,
+            contours: 0,
+        });
+    }
+
+    let x_min = points.iter().map(|p| p.0).min().unwrap() as i16;
+    let y_min = points.iter().map(|p| p.1).min().unwrap() as i16;
+    let x_max = points.iter().map(|p| p.0).max().unwrap() as i16;
+    let y_max = points.iter().map(|p| p.1).max().unwrap() as i16;
+
+    let mut data = Buffer::new();
+    data.i16(contours.len() as i16);
+    data.i16(x_min).i16(y_min).i16(x_max).i16(y_max);
+
+    let mut end = 0;
+
+    for contour in &contours {
+        end += contour.len();
+        data.u16(end as u16 - 1);
+    }
+
+    data.u16(0);
+
+    let mut flags = Vec::new();
+    let mut xs = Buffer::new();
+    let mut ys = Buffer::new();
+    let mut previous = (0, 0);
+
+    for (x, y, on_curve) in &points {
+        let mut flag = if *on_curve { 0x01 } else { 0x00 };
+
+        for (delta, buffer, short, same) in [
+            (x - previous.0, &mut xs, 0x02, 0x10),
+            (y - previous.1, &mut ys, 0x04, 0x20),
+        ] {
+            if delta == 0 {
+                flag |= same;
+            } else if delta.abs() < 256 {
+                flag |= short;
+
+                if delta > 0 {
+                    flag |= same;
+                }
+
+                buffer.u8(delta.unsigned_abs() as u8);
+            } else {
+                // Points are within range, but not always the jump between them.
+                let delta = i16::try_from(delta).map_err(|_| {
+                    format!(
+                        "Glyph point ({}, {}) is too far from the previous one",
+                        x, y
+                    )
+                })?;
+
+                buffer.i16(delta);
+            }
+        }
+
+        flags.push(flag);
+        previous = (*x, *y);
+    }
+
+    data.bytes(&flags).bytes(&xs.0).bytes(&ys.0);
+
+    // Glyphs are kept 4 bytes aligned.
+    while !data.0.len().is_multiple_of(4) {
+        data.u8(0);
+    }
+
+    Ok(Outline {
+        data: data.0,
+        bounds: (x_min, y_min, x_max, y_max),
+        points: points.len(),
+        contours: contours.len(),
+    })
+}
+
+/// Rounds the points of a contour, dropping repeated points and the end
+/// point when it closes the contour. Contours without area are dropped.
+fn finish_contour(contour: &[(f64, f64, bool)]) -> Result<Option<Vec<ContourPoint>>, String> {
+    let mut points: Vec<ContourPoint> = Vec::new();
+
+    for (x, y, on_curve) in contour {
+        let point: ContourPoint = (x.round() as i32, y.round() as i32, *on_curve);
+
+        if point.0.abs() > i16::MAX as i32 || point.1.abs() > i16::MAX as i32 {
+            return Err(format!("Glyph point ({}, {}) is out of range", x, y));
+        }
+
+        if points.last() != Some(&point) {
+            points.push(point);
+        }
+    }
+
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+
+    if points.len() < 3 {
+        return Ok(None);
+    }
+
+    Ok(Some(points))
+}
+
+/// Splits a cubic curve until each part is close enough to a quadratic
+/// curve, pushing their control and end points.
+fn cubic_to_quadratics(
+    p0: Point,
+    c1: Point,
+    c2: Point,
+    p3: Point,
+    tolerance: f64,
+    depth: usize,
+    quadratics: &mut Vec<(Point, Point)>,
+) {
+    // Largest distance between the cubic and its best quadratic approximation.
+    let error = 3f64.sqrt() / 36.0
+        * (p3.0 - 3.0 * c2.0 + 3.0 * c1.0 - p0.0).hypot(p3.1 - 3.0 * c2.1 + 3.0 * c1.1 - p0.1);
+
+    if error <= tolerance || depth >= 16 {
+        let control = (
+            (3.0 * (c1.0 + c2.0) - p0.0 - p3.0) / 4.0,
+            (3.0 * (c1.1 + c2.1) - p0.1 - p3.1) / 4.0,
+        );
+        quadratics.push((control, p3));
+        return;
+    }
+
+    let mid = |a: Point, b: Point| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let (a, b, c) = (mid(p0, c1), mid(c1, c2), mid(c2, p3));
+    let (d, e) = (mid(a, b), mid(b, c));
+    let middle = mid(d, e);
+
+    cubic_to_quadratics(p0, a, d, middle, tolerance, depth + 1, quadratics);
+    cubic_to_quadratics(middle, e, c, p3, tolerance, depth + 1, quadratics);
+}
+
+fn font_bounds(outlines: &[Outline]) -> (i16, i16, i16, i16) {
+    let drawn = outlines.iter().filter(|outline| outline.contours > 0);
+
+    (
+        drawn.clone().map(|o| o.bounds.0).min().unwrap_or(0),
+        drawn.clone().map(|o| o.bounds.1).min().unwrap_or(0),
+        drawn.clone().map(|o| o.bounds.2).max().unwrap_or(0),
+        drawn.map(|o| o.bounds.3).max().unwrap_or(0),
+    )
+}
+
+fn hmtx_table(outlines: &[Outline], advances: &[u16]) -> Vec<u8> {
+    let mut hmtx = Buffer::new();
+
+    for (outline, advance) in outlines.iter().zip(advances) {
+        hmtx.u16(*advance).i16(outline.bounds.0);
+    }
+
+    hmtx.0
+}
+
+fn maxp_table(outlines: &[Outline]) -> Vec<u8> {
+    let mut maxp = Buffer::new();
+    maxp.u32(0x00010000).u16(outlines.len() as u16);
+    maxp.u16(outlines.iter().map(|o| o.points).max().unwrap_or(0) as u16);
+    maxp.u16(outlines.iter().map(|o| o.contours).max().unwrap_or(0) as u16);
+    maxp.u16(0).u16(0).u16(2);
+
+    for _ in 0..8 {
+        maxp.u16(0);
+    }
+
+    maxp.0
+}
+
+/// Maps characters with a format 4 subtable for the Basic Multilingual
+/// Plane, and a format 12 one when characters are beyond it.
+fn cmap_table(mapping: &[(u32, u16)]) -> Vec<u8> {
+    // Runs of consecutive characters mapped to consecutive glyphs.
+    let mut groups: Vec<(u32, u32, u16)> = Vec::new();
+
+    for (character, glyph_id) in mapping {
+        match groups.last_mut() {
+            Some((start, end, first))
+                if *end + 1 == *character
+                    && (*first as u32 + *character - *start) == *glyph_id as u32
+                    && (*character <= 0xffff || *start > 0xffff) =>
+            {
+                *end = *character
+            }
+            _ => groups.push((*character, *character, *glyph_id)),
+        }
+    }
+
+    let mut segments = groups
+        .iter()
+        .filter(|(_, end, _)| *end < 0xffff)
+        .map(|(start, end, glyph_id)| (*start as u16, *end as u16, *glyph_id))
+        .collect::<Vec<(u16, u16, u16)>>();
+    segments.push((0xffff, 0xffff, 0));
+
+    let count = segments.len() as u16;
+    let (range, selector, shift) = search_parameters(count, 2);
+
+    let mut format4 = Buffer::new();
+    format4.u16(4).u16(16 + 8 * count).u16(0);
+    format4.u16(count * 2).u16(range).u16(selector).u16(shift);
+
+    for (_, end, _) in &segments {
+        format4.u16(*end);
+    }
+
+    format4.u16(0);
+
+    for (start, _, _) in &segments {
+        format4.u16(*start);
+    }
+
+    for (start, end, glyph_id) in &segments {
+        format4.u16(if *end == 0xffff {
+            1
+        } else {
+            glyph_id.wrapping_sub(*start)
+        });
+    }
+
+    for _ in &segments {
+        format4.u16(0);
+    }
+
+    let mut subtables = vec![((3, 1), format4.0)];
+
+    if groups.iter().any(|(_, end, _)| *end > 0xffff) {
+        let mut format12 = Buffer::new();
+        format12.u16(12).u16(0);
+        format12.u32(16 + 12 * groups.len() as u32).u32(0);
+        format12.u32(groups.len() as u32);
+
+        for (start, end, glyph_id) in &groups {
+            format12.u32(*start).u32(*end).u32(*glyph_id as u32);
+        }
+
+        subtables.push(((3, 10), format12.0));
+    }
+
+    let mut cmap = Buffer::new();
+    cmap.u16(0).u16(subtables.len() as u16);
+
+    let mut offset = 4 + 8 * subtables.len() as u32;
+
+    for ((platform, encoding), subtable) in &subtables {
+        cmap.u16(*platform).u16(*encoding).u32(offset);
+        offset += subtable.len() as u32;
+    }
+
+    for (_, subtable) in subtables {
+        cmap.bytes(&subtable);
+    }
+
+    cmap.0
+}
+
+/// A format 0 `kern` table for pairs sorted by glyph ids.
+fn kern_table(pairs: &[(u16, u16, i16)]) -> Result<Vec<u8>, String> {
+    let length = 14 + 6 * pairs.len();
+
+    if length > u16::MAX as usize {
+        return Err("Too many kerning pairs for a kern table".to_string());
+    }
+
+    let count = pairs.len() as u16;
+    let (range, selector, shift) = search_parameters(count, 6);
+
+    let mut kern = Buffer::new();
+    kern.u16(0).u16(1);
+    kern.u16(0).u16(length as u16).u16(0x0001);
+    kern.u16(count).u16(range).u16(selector).u16(shift);
+
+    for (left, right, value) in pairs {
+        kern.u16(*left).u16(*right).i16(*value);
+    }
+
+    Ok(kern.0)
+}
+
+/// Assembles tables into a font file, with their checksums.
+fn sfnt(mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let (range, selector, shift) = search_parameters(tables.len() as u16, 16);
+
+    let mut font = Buffer::new();
+    font.u32(0x00010000).u16(tables.len() as u16);
+    font.u16(range).u16(selector).u16(shift);
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = 0;
+
+    for (tag, table) in &tables {
+        if tag == b"head" {
+            head_offset = offset;
+        }
+
+        font.bytes(tag).u32(checksum(table)).u32(offset as u32);
+        font.u32(table.len() as u32);
+        offset += table.len().div_ceil(4) * 4;
+    }
+
+    for (_, table) in &tables {
+        font.bytes(table);
+
+        while !font.0.len().is_multiple_of(4) {
+            font.u8(0);
+        }
+    }
+
+    let adjustment = 0xb1b0afba_u32.wrapping_sub(checksum(&font.0));
+    font.0[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+
+    font.0
+}
+
+/// The search range, entry selector and range shift of binary searchable
+/// arrays of `count` items of `size` bytes.
+fn search_parameters(count: u16, size: u16) -> (u16, u16, u16) {
+    let mut power = 1;
+    let mut selector = 0;
+
+    while power * 2 <= count {
+        power *= 2;
+        selector += 1;
+    }
+
+    (
+        power * size,
+        selector,
+        (count * size).saturating_sub(power * size),
+    )
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn clamp(value: f64) -> i16 {
+    value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+}
+
+/// Big endian binary data.
+struct Buffer(Vec<u8>);
+
+impl Buffer {
+    fn new() -> Self {
+        Buffer(Vec::new())
+    }
+
+    fn u8(&mut self, value: u8) -> &mut Self {
+        self.0.push(value);
+        self
+    }
+
+    fn u16(&mut self, value: u16) -> &mut Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn i16(&mut self, value: i16) -> &mut Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_compiles_a_font_that_loads_back() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string())
+            .unwrap()
+            .subset_text("Hello World");
+        let compiled =
+            Font::from_opentype(&font.to_truetype(&TrueTypeOptions::default()).unwrap()).unwrap();
+
+        assert_eq!(compiled.family(), Some("Microsoft Sans Serif"));
+        assert_eq!(compiled.units_per_em(), 2048.0);
+        assert_eq!(compiled.ascent(), 1638.0);
+        assert_eq!(compiled.descent(), -410.0);
+        assert_eq!(compiled.weight(), 400.0);
+        assert_eq!(compiled.glyphs().len(), 8);
+        assert_eq!(compiled.missing_glyph_advance(), Some(600.0));
+        assert_eq!(compiled.glyph_for('H').unwrap().name.as_deref(), Some("H"));
+        assert_eq!(
+            compiled.text_width("Hello World".to_string()),
+            font.text_width("Hello World".to_string())
+        );
+    }
+
+    #[test]
+    fn it_approximates_curves_within_the_tolerance() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font id="Circles" horiz-adv-x="1000">
+                    <font-face font-style="italic" units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph glyph-name="circle" unicode="o" d="M0 500A500 500 0 1 1 1000 500A500 500 0 1 1 0 500Z" />
+                    <glyph glyph-name="bold-a" unicode="&#x1d400;" horiz-adv-x="700" d="M0 0L350 700L700 0Z" />
+                    <hkern u1="o" u2="&#x1d400;" k="50" />
+                </font>
+            </svg>"#;
+
+        let font = Font::new(data.to_string()).unwrap();
+        let options = TrueTypeOptions {
+            tolerance: 0.5,
+            kerning: true,
+        };
+        let compiled = Font::from_opentype(&font.to_truetype(&options).unwrap()).unwrap();
+
+        assert_eq!(compiled.family(), Some("Circles"));
+        assert_eq!(compiled.style(), FontStyle::Italic);
+        assert_eq!(compiled.text_width("\u{1d400}".to_string()), 700.0);
+        assert_eq!(compiled.kerning('o', '\u{1d400}'), -50.0);
+
+        let circle = Path::new(compiled.glyph_for('o').unwrap().path.clone()).unwrap();
+        let distance = |(x, y): Point| ((x - 500.0).hypot(y - 500.0) - 500.0).abs();
+        let mut current = (0.0, 0.0);
+        let mut curves = 0;
+
+        for element in circle.elements() {
+            match element {
+                Element::MoveTo(point) | Element::LineTo(point) => current = point,
+                Element::QuadTo(control, point) => {
+                    let middle = (
+                        0.25 * current.0 + 0.5 * control.0 + 0.25 * point.0,
+                        0.25 * current.1 + 0.5 * control.1 + 0.25 * point.1,
+                    );
+
+                    // Points are rounded to integers.
+                    assert!(distance(point) <= 1.0);
+                    assert!(distance(middle) <= 1.5);
+
+                    current = point;
+                    curves += 1;
+                }
+                _ => {}
+            }
+        }
+
+        assert!(curves >= 8);
+
+        let without_kerning = TrueTypeOptions {
+            tolerance: 0.5,
+            kerning: false,
+        };
+        let compiled = Font::from_opentype(&font.to_truetype(&without_kerning).unwrap()).unwrap();
+
+        assert_eq!(compiled.kerning('o', '\u{1d400}'), 0.0);
+    }
+
+    #[test]
+    fn it_fails_on_jumps_between_points_out_of_range() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="1000">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="a" d="M-30000 0L30000 0L30000 100Z" />
+                </font>
+            </svg>"#;
+
+        let font = Font::new(data.to_string()).unwrap();
+
+        assert_eq!(
+            font.to_truetype(&TrueTypeOptions::default()),
+            Err("Glyph point (30000, 0) is too far from the previous one".to_string())
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::font::Font;
+use super::layout::TextOptions;
+
+const ELLIPSIS: &str = "…";
+const ELLIPSIS_FALLBACK: &str = "...";
+
+/// Where the ellipsis is placed when a text is truncated, like CSS
+/// `text-overflow: ellipsis` applied at the end, the start or the middle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncateAt {
+    End,
+    Start,
+    Middle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Truncation {
+    /// The text to display, ellipsis included when something was dropped.
+    pub text: String,
+    /// Number of user-perceived characters (grapheme clusters) removed.
+    pub dropped: usize,
+    /// Width of `text`, measured with `Font::text_width_with`.
+    pub width: f64,
+}
+
+impl Font {
+    /// Truncates `text` so that it fits within `max_width` (in font units).
+    ///
+    /// The font's `…` glyph is used as ellipsis, falling back to "..." when the
+    /// font doesn't provide it. Text is only ever cut on grapheme boundaries.
+    pub fn truncate(&self, text: &str, max_width: f64, at: TruncateAt) -> Truncation {
+        self.truncate_with(text, max_width, at, &TextOptions::default())
+    }
+
+    /// Truncates `text` as `truncate` does, measuring it as laid out with
+    /// `options`, spacing and tabs included.
+    pub fn truncate_with(
+        &self,
+        text: &str,
+        max_width: f64,
+        at: TruncateAt,
+        options: &TextOptions,
+    ) -> Truncation {
+        let width = self.text_width_with(text, options);
+
+        if width <= max_width {
+            return Truncation {
+                text: text.to_string(),
+                dropped: 0,
+                width,
+            };
+        }
+
+        let ellipsis = self.ellipsis();
+        let graphemes = text.graphemes(true).collect::<Vec<&str>>();
+
+        for kept in (0..graphemes.len()).rev() {
+            let candidate = match at {
+                TruncateAt::End => format!("{}{}", graphemes[..kept].concat(), ellipsis),
+                TruncateAt::Start => format!(
+                    "{}{}",
+                    ellipsis,
+                    graphemes[graphemes.len() - kept..].concat()
+                ),
+                TruncateAt::Middle => {
+                    let head = kept.div_ceil(2);
+                    let tail = kept - head;
+
+                    format!(
+                        "{}{}{}",
+                        graphemes[..head].concat(),
+                        ellipsis,
+                        graphemes[graphemes.len() - tail..].concat()
+                    )
+                }
+            };
+
+            let width = self.text_width_with(&candidate, options);
+
+            if width <= max_width || kept == 0 {
+                return Truncation {
+                    text: candidate,
+                    dropped: graphemes.len() - kept,
+                    width,
+                };
+            }
+        }
+
+        let ellipsis = ellipsis.to_string();
+
+        Truncation {
+            width: self.text_width_with(&ellipsis, options),
+            text: ellipsis,
+            dropped: graphemes.len(),
+        }
+    }
+
+    fn ellipsis(&self) -> &'static str {
+        if ELLIPSIS.chars().all(|character| self.has_glyph(character)) {
+            ELLIPSIS
+        } else {
+            ELLIPSIS_FALLBACK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::layout::Length;
+    use crate::svg::testing::ARIAL_SVG_FONT;
+
+    #[test]
+    fn it_keeps_text_that_already_fits() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        let truncation = font.truncate("Hello", 10000.0, TruncateAt::End);
+
+        assert_eq!(truncation.text, "Hello");
+        assert_eq!(truncation.dropped, 0);
+        assert_eq!(truncation.width, font.text_width("Hello".to_string()));
+    }
+
+    #[test]
+    fn it_truncates_at_the_end_with_the_ellipsis_glyph() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let max_width = font.text_width("Hello…".to_string());
+
+        let truncation = font.truncate("Hello World", max_width, TruncateAt::End);
+
+        assert_eq!(truncation.text, "Hello…");
+        assert_eq!(truncation.dropped, 6);
+        assert_eq!(truncation.width, max_width);
+    }
+
+    #[test]
+    fn it_truncates_at_the_start_and_in_the_middle() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+
+        let start = font.truncate(
+            "abcdefgh",
+            font.text_width("…fgh".to_string()),
+            TruncateAt::Start,
+        );
+        let middle = font.truncate(
+            "abcdefgh",
+            font.text_width("ab…gh".to_string()),
+            TruncateAt::Middle,
+        );
+
+        assert_eq!(start.text, "…fgh");
+        assert_eq!(start.dropped, 5);
+        assert_eq!(middle.text, "ab…gh");
+        assert_eq!(middle.dropped, 4);
+    }
+
+    #[test]
+    fn it_measures_with_the_text_options() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let options = TextOptions {
+            letter_spacing: Length::Em(0.05),
+            ..TextOptions::default()
+        };
+        let max_width = font.text_width("Hello W…".to_string());
+
+        let truncation = font.truncate_with("Hello World", max_width, TruncateAt::End, &options);
+
+        assert_eq!(truncation.text, "Hello …");
+        assert_eq!(truncation.width, font.text_width_with("Hello …", &options));
+        assert!(truncation.width <= max_width);
+        assert!(font.text_width_with("Hello W…", &options) > max_width);
+    }
+
+    #[test]
+    fn it_never_splits_a_grapheme_cluster() {
+        let font = Font::new(ARIAL_SVG_FONT.to_string()).unwrap();
+        let max_width = font.text_width("e\u{301}…".to_string());
+
+        let truncation = font.truncate("e\u{301}e\u{301}e\u{301}", max_width, TruncateAt::End);
+
+        assert_eq!(truncation.text, "e\u{301}…");
+        assert_eq!(truncation.dropped, 2);
+    }
+
+    #[test]
+    fn it_falls_back_to_three_dots_without_an_ellipsis_glyph() {
+        let data = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <font horiz-adv-x="500">
+                    <font-face units-per-em="1000" ascent="800" descent="-200" />
+                    <glyph unicode="a" d="M0 0h500v500h-500z" />
+                    <glyph unicode="." horiz-adv-x="100" d="M0 0h100v100h-100z" />
+                </font>
+            </svg>"#;
+        let font = Font::new(data.to_string()).unwrap();
+
+        let truncation = font.truncate("aaaa", 800.0, TruncateAt::End);
+
+        assert_eq!(truncation.text, "a...");
+        assert_eq!(truncation.dropped, 3);
+        assert_eq!(truncation.width, 800.0);
+    }
+}